@@ -1,12 +1,30 @@
-use egui::{Color32, RichText, Ui, Widget};
+use std::{fmt::Display, str::FromStr};
+
+use egui::{
+    plot::{HLine, Legend, Line, Plot, PlotPoint, Points, Polygon, Text},
+    Color32, RichText, TextEdit, Ui, Widget,
+};
 use meval::Expr;
 use opencrunch_derive::crunch_fill;
 use statrs::{
-    distribution::{ChiSquared, ContinuousCDF, FisherSnedecor, Normal, StudentsT},
+    distribution::{
+        Beta, Binomial, ChiSquared, ContinuousCDF, Discrete, DiscreteCDF, FisherSnedecor, Normal,
+        Poisson, StudentsT,
+    },
     function,
 };
 
-use crate::{empty_resp, Constr, GridNumBox, NumBox};
+use crate::{
+    empty_resp, fmt_num,
+    hyptest::{solve_confidence, solve_n, Alternative, HypothesisTest, NullDist},
+    levels,
+    linalg::lstsq,
+    parse_data, parse_labels,
+    power::Power,
+    rv::{DiscreteRv, LinComb, NormalApprox},
+    sim::{Bootstrap, Coverage, MonteCarlo, Permutation, SamplingDist},
+    Constr, DataBox, GridNumBox, NumBox, TableView,
+};
 
 #[derive(Default, Clone)]
 enum Calcs {
@@ -26,6 +44,21 @@ enum Calcs {
     KStats(KStats),
     SampleStat(SampleStat),
     RCTable(RCTable),
+    TwoWayAnova(TwoWayAnova),
+    LinReg(LinReg),
+    MultiReg(MultiReg),
+    GoodnessOfFit(GoodnessOfFit),
+    NonParam(NonParam),
+    RankCorr(RankCorr),
+    Power(Power),
+    MonteCarlo(MonteCarlo),
+    SamplingDist(SamplingDist),
+    Coverage(Coverage),
+    Bootstrap(Bootstrap),
+    Permutation(Permutation),
+    DiscreteRv(DiscreteRv),
+    LinComb(LinComb),
+    NormalApprox(NormalApprox),
 }
 
 #[derive(Default)]
@@ -33,6 +66,16 @@ pub(crate) struct OpenCrunchCalcs {
     sample: Calcs,
 }
 
+impl OpenCrunchCalcs {
+    /// A normal distribution a calculator asked to open in the distributions tab.
+    pub(crate) fn take_normal(&mut self) -> Option<Normal> {
+        match &mut self.sample {
+            Calcs::LinComb(l) => l.take_send(),
+            _ => None,
+        }
+    }
+}
+
 impl Widget for &mut OpenCrunchCalcs {
     fn ui(self, ui: &mut Ui) -> egui::Response {
         ui.horizontal(|ui| {
@@ -80,6 +123,62 @@ impl Widget for &mut OpenCrunchCalcs {
             if ui.button("RxC Table").clicked() {
                 self.sample = Calcs::RCTable(RCTable::default());
             }
+            if ui.button("Goodness of Fit").clicked() {
+                self.sample = Calcs::GoodnessOfFit(GoodnessOfFit::default());
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Two-way ANOVA").clicked() {
+                self.sample = Calcs::TwoWayAnova(TwoWayAnova::default());
+            }
+            if ui.button("Regression").clicked() {
+                self.sample = Calcs::LinReg(LinReg::default());
+            }
+            if ui.button("Multiple Regression").clicked() {
+                self.sample = Calcs::MultiReg(MultiReg::default());
+            }
+            if ui.button("Power").clicked() {
+                self.sample = Calcs::Power(Power::default());
+            }
+            if ui.button("Monte Carlo").clicked() {
+                self.sample = Calcs::MonteCarlo(MonteCarlo::default());
+            }
+            if ui.button("Sampling Distribution").clicked() {
+                self.sample = Calcs::SamplingDist(SamplingDist::default());
+            }
+            if ui.button("CI Coverage").clicked() {
+                self.sample = Calcs::Coverage(Coverage::default());
+            }
+            if ui.button("Bootstrap").clicked() {
+                self.sample = Calcs::Bootstrap(Bootstrap::default());
+            }
+            if ui.button("Permutation Test").clicked() {
+                self.sample = Calcs::Permutation(Permutation::default());
+            }
+            if ui.button("Discrete RV").clicked() {
+                self.sample = Calcs::DiscreteRv(DiscreteRv::default());
+            }
+            if ui.button("Linear Combination").clicked() {
+                self.sample = Calcs::LinComb(LinComb::default());
+            }
+            if ui.button("Normal Approximation").clicked() {
+                self.sample = Calcs::NormalApprox(NormalApprox::default());
+            }
+        });
+        ui.horizontal(|ui| {
+            for (t, name) in [
+                (NpTest::MannWhitney, "Mann-Whitney"),
+                (NpTest::SignedRank, "Wilcoxon Signed-Rank"),
+                (NpTest::KruskalWallis, "Kruskal-Wallis"),
+                (NpTest::Sign, "Sign Test"),
+            ] {
+                if ui.button(name).clicked() {
+                    self.sample = Calcs::NonParam(NonParam::new(t));
+                }
+            }
+            if ui.button("Correlation").clicked() {
+                self.sample = Calcs::RankCorr(RankCorr::default());
+            }
         });
 
         match &mut self.sample {
@@ -98,32 +197,73 @@ impl Widget for &mut OpenCrunchCalcs {
             Calcs::KStats(k) => ui.add(k),
             Calcs::SampleStat(s) => ui.add(s),
             Calcs::RCTable(r) => ui.add(r),
+            Calcs::TwoWayAnova(a) => ui.add(a),
+            Calcs::LinReg(r) => ui.add(r),
+            Calcs::MultiReg(r) => ui.add(r),
+            Calcs::GoodnessOfFit(g) => ui.add(g),
+            Calcs::NonParam(n) => ui.add(n),
+            Calcs::RankCorr(r) => ui.add(r),
+            Calcs::Power(p) => ui.add(p),
+            Calcs::MonteCarlo(m) => ui.add(m),
+            Calcs::SamplingDist(s) => ui.add(s),
+            Calcs::Coverage(c) => ui.add(c),
+            Calcs::Bootstrap(b) => ui.add(b),
+            Calcs::Permutation(p) => ui.add(p),
+            Calcs::DiscreteRv(d) => ui.add(d),
+            Calcs::LinComb(l) => ui.add(l),
+            Calcs::NormalApprox(n) => ui.add(n),
         }
     }
 }
 
-impl ToString for OpenCrunchCalcs {
-    fn to_string(&self) -> String {
-        match self.sample {
-            Calcs::None => "OpenCrunch - Calcs".to_owned(),
-            Calcs::SampInf(_) => "OpenCrunch - Calcs - Sample".to_owned(),
-            Calcs::SampFin(_) => "OpenCrunch - Calcs - Sample Finite".to_owned(),
-            Calcs::Comb(_) => "OpenCrunch - Calcs - Combinatorics".to_owned(),
-            Calcs::Calc(_) => "OpenCrunch - Calcs - Calculator".to_owned(),
-            Calcs::Cheby(_) => "OpenCrunch - Calcs - Chebyshev".to_owned(),
-            Calcs::ZOneStats(_) => "OpenCrunch - Calcs - Z Stats".to_owned(),
-            Calcs::TOneStats(_) => "OpenCrunch - Calcs - T Stats".to_owned(),
-            Calcs::ZTwoStats(_) => "OpenCrunch - Calcs - 2 Z Stats".to_owned(),
-            Calcs::TTwoStats(_) => "OpenCrunch - Calcs - 2 T Stats".to_owned(),
-            Calcs::VarOneStats(_) => "OpenCrunch - Calcs - Var Stats".to_owned(),
-            Calcs::VarTwoStats(_) => "OpenCrunch - Calcs - 2 Var Stats".to_owned(),
-            Calcs::KStats(_) => "OpenCrunch - Calcs - K Stats".to_owned(),
-            Calcs::RCTable(_) => "OpenCrunch - Calcs - RxC Table".to_owned(),
-            Calcs::SampleStat(_) => "OpenCrunch - Calcs - Sample Stats".to_owned(),
-        }
+impl Display for OpenCrunchCalcs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match &self.sample {
+            Calcs::None => "OpenCrunch - Calcs",
+            Calcs::SampInf(_) => "OpenCrunch - Calcs - Sample",
+            Calcs::SampFin(_) => "OpenCrunch - Calcs - Sample Finite",
+            Calcs::Comb(_) => "OpenCrunch - Calcs - Combinatorics",
+            Calcs::Calc(_) => "OpenCrunch - Calcs - Calculator",
+            Calcs::Cheby(_) => "OpenCrunch - Calcs - Chebyshev",
+            Calcs::ZOneStats(_) => "OpenCrunch - Calcs - Z Stats",
+            Calcs::TOneStats(_) => "OpenCrunch - Calcs - T Stats",
+            Calcs::ZTwoStats(_) => "OpenCrunch - Calcs - 2 Z Stats",
+            Calcs::TTwoStats(_) => "OpenCrunch - Calcs - 2 T Stats",
+            Calcs::VarOneStats(_) => "OpenCrunch - Calcs - Var Stats",
+            Calcs::VarTwoStats(_) => "OpenCrunch - Calcs - 2 Var Stats",
+            Calcs::KStats(_) => "OpenCrunch - Calcs - K Stats",
+            Calcs::RCTable(_) => "OpenCrunch - Calcs - RxC Table",
+            Calcs::SampleStat(_) => "OpenCrunch - Calcs - Sample Stats",
+            Calcs::TwoWayAnova(_) => "OpenCrunch - Calcs - Two-way ANOVA",
+            Calcs::LinReg(_) => "OpenCrunch - Calcs - Regression",
+            Calcs::MultiReg(_) => "OpenCrunch - Calcs - Multiple Regression",
+            Calcs::GoodnessOfFit(_) => "OpenCrunch - Calcs - Goodness of Fit",
+            Calcs::NonParam(n) => match n.test {
+                NpTest::MannWhitney => "OpenCrunch - Calcs - Mann-Whitney",
+                NpTest::SignedRank => "OpenCrunch - Calcs - Wilcoxon Signed-Rank",
+                NpTest::KruskalWallis => "OpenCrunch - Calcs - Kruskal-Wallis",
+                NpTest::Sign => "OpenCrunch - Calcs - Sign Test",
+            },
+            Calcs::RankCorr(_) => "OpenCrunch - Calcs - Correlation",
+            Calcs::Power(_) => "OpenCrunch - Calcs - Power",
+            Calcs::MonteCarlo(_) => "OpenCrunch - Calcs - Monte Carlo",
+            Calcs::SamplingDist(_) => "OpenCrunch - Calcs - Sampling Distribution",
+            Calcs::Coverage(_) => "OpenCrunch - Calcs - CI Coverage",
+            Calcs::Bootstrap(_) => "OpenCrunch - Calcs - Bootstrap",
+            Calcs::Permutation(_) => "OpenCrunch - Calcs - Permutation Test",
+            Calcs::DiscreteRv(_) => "OpenCrunch - Calcs - Discrete Random Variable",
+            Calcs::LinComb(_) => "OpenCrunch - Calcs - Linear Combination",
+            Calcs::NormalApprox(_) => "OpenCrunch - Calcs - Normal Approximation",
+        };
+        f.write_str(name)
     }
 }
 
+/// Sampling distribution of the mean of n draws from an infinite population.
+pub(crate) fn mean_dist(mean: f64, sd: f64, n: usize) -> Result<Normal, statrs::StatsError> {
+    Normal::new(mean, sd / (n as f64).sqrt())
+}
+
 #[crunch_fill]
 #[derive(Clone)]
 pub(crate) struct SampleProbInf {
@@ -170,7 +310,7 @@ impl Widget for &mut SampleProbInf {
         ui.label(RichText::new(&self.strings[5]).color(Color32::DARK_RED));
         if resp.changed() {
             //self.strings[4] = self.comp.to_string();
-            match Normal::new(self.mean, self.sd / ((self.sample_size as f64).sqrt())) {
+            match mean_dist(self.mean, self.sd, self.sample_size) {
                 Ok(n) => {
                     let fill = match self.target_mean {
                         Constr::GE(v) | Constr::GT(v) => 1.0 - n.cdf(v),
@@ -429,6 +569,203 @@ impl Widget for &mut Cheby {
     }
 }
 
+trait TestResults {
+    fn test_results(&mut self, stat: &str, df: &str, region: &Constr<f32>, conclusion: &str);
+}
+
+impl TestResults for Ui {
+    /// Shows the statistic, critical values, rejection region and decision of a test.
+    fn test_results(&mut self, stat: &str, df: &str, region: &Constr<f32>, conclusion: &str) {
+        if stat.is_empty() {
+            return;
+        }
+        self.num_box("statistic", &mut stat.to_string());
+        if !df.is_empty() {
+            self.num_box("df", &mut df.to_string());
+        }
+        let mut crit = match region {
+            Constr::GT(v) | Constr::LT(v) => v.to_string(),
+            Constr::Out(a, b) => format!("{a}, {b}"),
+            _ => "".to_string(),
+        };
+        self.num_box("critical value", &mut crit);
+        self.num_box("rejection region", &mut region.to_string());
+        self.label(conclusion);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum CiSolve {
+    #[default]
+    Interval,
+    Confidence,
+    N,
+}
+
+impl FromStr for CiSolve {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "interval" => Ok(CiSolve::Interval),
+            "confidence" => Ok(CiSolve::Confidence),
+            "n" => Ok(CiSolve::N),
+            _ => Err("Not a valid thing to solve for"),
+        }
+    }
+}
+
+impl Display for CiSolve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CiSolve::Interval => "interval",
+            CiSolve::Confidence => "confidence",
+            CiSolve::N => "n",
+        })
+    }
+}
+
+trait CiInputs {
+    fn ci_solve(&mut self, solve: &mut CiSolve, kind: &mut Constr<f32>) -> bool;
+    fn solve_box(&mut self, l: &str, v: &mut String, solved: bool) -> egui::Response;
+}
+
+impl CiInputs for Ui {
+    /// Picks what to solve for, and whether the interval is two sided or a bound.
+    fn ci_solve(&mut self, solve: &mut CiSolve, kind: &mut Constr<f32>) -> bool {
+        let mut changed = false;
+        self.horizontal(|ui| {
+            ui.label("Solve for");
+            for (s, name) in [
+                (CiSolve::Interval, "interval"),
+                (CiSolve::Confidence, "confidence"),
+                (CiSolve::N, "sample size"),
+            ] {
+                changed |= ui.radio_value(solve, s, name).changed();
+            }
+        });
+        let side = if !kind.is_ineq() || kind.is_range() {
+            0
+        } else if matches!(
+            kind,
+            Constr::GE(_) | Constr::GT(_) | Constr::GENone | Constr::GTNone
+        ) {
+            1
+        } else {
+            2
+        };
+        let mut new = side;
+        self.horizontal(|ui| {
+            ui.label("Interval");
+            for (i, name) in [(0, "two sided"), (1, "lower bound"), (2, "upper bound")] {
+                ui.radio_value(&mut new, i, name);
+            }
+        });
+        if new != side {
+            *kind = match new {
+                1 => Constr::GENone,
+                2 => Constr::LENone,
+                _ => Constr::None,
+            };
+            changed = true;
+        }
+        changed
+    }
+
+    /// An input, or a read only box when the value is being solved for.
+    fn solve_box(&mut self, l: &str, v: &mut String, solved: bool) -> egui::Response {
+        if solved {
+            self.num_box(l, &mut v.clone())
+        } else {
+            self.num_box(l, v)
+        }
+    }
+}
+
+/// Squares the ends of an interval of positive values.
+fn squared(c: Constr<f32>) -> Constr<f32> {
+    match c {
+        Constr::In(a, b) => Constr::In(a * a, b * b),
+        Constr::GE(a) => Constr::GE(a * a),
+        Constr::LE(b) => Constr::LE(b * b),
+        c => c,
+    }
+}
+
+/// Works out whichever of the interval, confidence and sample size is being solved for,
+/// returning the interval, the confidence and any solved sample size.
+fn solve_ci<T: HypothesisTest + Clone>(
+    t: &T,
+    solve: CiSolve,
+    kind: &Constr<f32>,
+    confidence: f64,
+    margin: &str,
+    set_n: impl Fn(&mut T, usize),
+) -> Result<(Constr<f32>, f64, Option<usize>), String> {
+    let target = || {
+        if margin.trim().is_empty() {
+            t.reach(kind)
+        } else {
+            margin
+                .parse::<Expr>()
+                .ok()
+                .and_then(|e| e.eval().ok())
+                .ok_or("Margin of error is invalid".to_string())
+        }
+    };
+    match solve {
+        CiSolve::Interval => Ok((t.bounds(kind, confidence)?, confidence, None)),
+        CiSolve::Confidence => {
+            let c = solve_confidence(t, kind, target()?)?;
+            Ok((t.bounds(kind, c)?, c, None))
+        }
+        CiSolve::N => {
+            let n = solve_n(t, &set_n, kind, confidence, target()?)?;
+            let mut t = t.clone();
+            set_n(&mut t, n);
+            Ok((t.bounds(kind, confidence)?, confidence, Some(n)))
+        }
+    }
+}
+
+trait RawInput {
+    fn raw_input(&mut self, raw: &mut bool) -> egui::Response;
+}
+
+impl RawInput for Ui {
+    /// Switches between typed summaries and raw data, keeping what was entered in both.
+    fn raw_input(&mut self, raw: &mut bool) -> egui::Response {
+        self.horizontal(|ui| {
+            ui.label("Input");
+            let resp = ui.radio_value(raw, false, "summary");
+            resp.union(ui.radio_value(raw, true, "raw data"))
+        })
+        .inner
+    }
+}
+
+/// Mean, standard deviation and size of a column of data.
+fn summarize(data: &str) -> Result<(f64, f64, usize), String> {
+    let v = parse_data(data)?;
+    if v.len() < 2 {
+        return Err("Not enough values".to_string());
+    }
+    let n = v.len() as f64;
+    let mean = v.iter().sum::<f64>() / n;
+    let var = v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    Ok((mean, var.sqrt(), v.len()))
+}
+
+/// A number as a field value.
+fn expr(x: f64) -> Expr {
+    x.to_string().parse().unwrap()
+}
+
+/// Value of a field for a read only box.
+fn expr_str(e: &Expr) -> String {
+    e.eval().map(fmt_num).unwrap_or_default()
+}
+
 #[crunch_fill]
 #[derive(Clone)]
 pub(crate) struct ZOneStats {
@@ -439,6 +776,12 @@ pub(crate) struct ZOneStats {
     interval: Constr<f32>,
     hypothesis: Constr<f32>,
     pval: f32,
+    alpha: f32,
+    stat: f32,
+    region: Constr<f32>,
+    conclusion: String,
+    solve: CiSolve,
+    margin: String,
 }
 
 impl Default for ZOneStats {
@@ -447,6 +790,16 @@ impl Default for ZOneStats {
             sample_mean: "0.0".parse().unwrap(),
             sample_dev: "1.0".parse().unwrap(),
             sample_size: 30,
+            confidence: 0.95,
+            interval: Constr::In(-1.96, 1.96),
+            hypothesis: Constr::NE(0.0),
+            pval: 0.05,
+            alpha: 0.05,
+            stat: 0.0,
+            region: Constr::None,
+            conclusion: "".to_string(),
+            solve: CiSolve::Interval,
+            margin: "".to_string(),
             strings: [
                 "0.0".to_string(),
                 "1.0".to_string(),
@@ -455,96 +808,132 @@ impl Default for ZOneStats {
                 "[-1.96, 1.96]".to_string(),
                 "!=0.0".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "interval".to_string(),
                 "".to_string(),
             ],
-            confidence: 0.95,
-            interval: Constr::In(-1.96, 1.96),
-            hypothesis: Constr::LENone,
-            pval: 0.05,
         }
     }
 }
 
+impl ZOneStats {
+    /// Sample mean and its standard error.
+    fn mean_se(&self) -> Result<(f64, f64), String> {
+        let mean = self.sample_mean.eval().map_err(|_| "Mean is invalid")?;
+        let dev = self
+            .sample_dev
+            .eval()
+            .map_err(|_| "Standard deviation is invalid")?;
+        let se = dev / (self.sample_size as f64).sqrt();
+        if se.is_nan() || se <= 0.0 {
+            return Err("Standard error must be positive".to_string());
+        }
+        Ok((mean, se))
+    }
+}
+
+impl HypothesisTest for ZOneStats {
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let (mean, se) = self.mean_se()?;
+        Ok(((mean - h0) / se, NullDist::Normal))
+    }
+
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String> {
+        let (mean, se) = self.mean_se()?;
+        let q = NullDist::Normal.inverse_cdf((1.0 + confidence) / 2.0)? * se;
+        Ok(Constr::In((mean - q) as f32, (mean + q) as f32))
+    }
+
+    fn estimate(&self) -> Result<f64, String> {
+        Ok(self.mean_se()?.0)
+    }
+}
+
 impl Widget for &mut ZOneStats {
     fn ui(self, ui: &mut Ui) -> egui::Response {
         let mut resp = ui.num_box("mean", &mut self.strings[0]);
         resp = resp.union(ui.num_box("sd", &mut self.strings[1]));
-        resp = resp.union(ui.num_box("sample size", &mut self.strings[2]));
-        resp = resp.union(ui.num_box("confidence", &mut self.strings[3]));
+        if ui.ci_solve(&mut self.solve, &mut self.interval) {
+            self.strings[11] = self.solve.to_string();
+            self.strings[4] = self.interval.to_string();
+            resp.mark_changed();
+        }
+        resp = resp.union(ui.solve_box(
+            "sample size",
+            &mut self.strings[2],
+            self.solve == CiSolve::N,
+        ));
+        resp = resp.union(ui.solve_box(
+            "confidence",
+            &mut self.strings[3],
+            self.solve == CiSolve::Confidence,
+        ));
+        resp = resp.union(ui.solve_box(
+            "interval",
+            &mut self.strings[4],
+            self.solve == CiSolve::Interval,
+        ));
+        if self.solve != CiSolve::Interval {
+            resp = resp.union(ui.num_box("or margin of error", &mut self.strings[12]));
+        }
         if resp.changed() {
             self.vfill();
-            let mut f = || {
-                let Ok(mean) = self.sample_mean.eval() else {
-                self.strings[7] = "Mean is invalid".to_owned();
-                return;
-            };
-                let Ok(dev) = self.sample_dev.eval() else {
-                self.strings[7] = "Standard deviation is invalid".to_owned();
-                return;
-            };
-
-                let std_err = dev / (self.sample_size as f64).sqrt();
-
-                let Ok(n) = Normal::new(mean, std_err) else {
-                self.strings[7] = "Not a valid normal distr".to_string();
-                return;
-            };
-
-                let int_l = n.inverse_cdf((1.0 - self.confidence as f64) / 2.0);
-                let int_h = n.inverse_cdf((1.0 + self.confidence as f64) / 2.0);
-
-                self.interval = Constr::In(int_l as f32, int_h as f32);
-
-                self.strings[4] = self.interval.to_string();
-                self.strings[7].clear();
-            };
-            f();
+            match solve_ci(
+                &*self,
+                self.solve,
+                &self.interval,
+                self.confidence as f64,
+                &self.margin,
+                |t, n| t.sample_size = n,
+            ) {
+                Ok((ci, c, n)) => {
+                    match self.solve {
+                        CiSolve::Interval => {
+                            self.interval = ci;
+                            self.strings[4] = ci.to_string();
+                        }
+                        CiSolve::Confidence => {
+                            self.confidence = c as f32;
+                            self.strings[3] = fmt_num(c);
+                        }
+                        CiSolve::N => {}
+                    }
+                    if let Some(n) = n {
+                        self.sample_size = n;
+                        self.strings[2] = n.to_string();
+                    }
+                    self.strings[13].clear();
+                }
+                Err(e) => self.strings[13] = e,
+            }
         }
-        ui.num_box("", &mut self.strings[4].clone());
         ui.label("Hypothesis");
         resp = resp.union(ui.num_box("H1", &mut self.strings[5]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[7]));
         if resp.changed() {
             self.vfill();
-            let mut f = || {
-                let Ok(mean) = self.sample_mean.eval() else {
-                self.strings[7] = "Mean is invalid".to_owned();
-                return;
-            };
-                let Ok(dev) = self.sample_dev.eval() else {
-                self.strings[7] = "Standard deviation is invalid".to_owned();
-                return;
-            };
-
-                let std_err = dev / (self.sample_size as f64).sqrt();
-
-                let Ok(n) = Normal::new(mean, std_err) else {
-                self.strings[7] = "Not a valid normal distr".to_string();
-                return;
-            };
-
-                self.pval = match self.hypothesis {
-                    Constr::GE(v) | Constr::GT(v) => n.cdf(v as f64),
-                    Constr::LE(v) | Constr::LT(v) => 1. - n.cdf(v as f64),
-                    Constr::NE(v) => {
-                        if mean > v as f64 {
-                            2.0 * n.cdf(v as f64)
-                        } else {
-                            2.0 - 2.0 * n.cdf(v as f64)
-                        }
-                    }
-                    _ => {
-                        self.strings[7] = "Not valid hypothesis".to_owned();
-                        return;
-                    }
-                } as f32;
-
-                self.strings[6] = self.pval.to_string();
-                self.strings[7].clear();
-            };
-            f();
+            self.strings[8].clear();
+            match self.run(&self.hypothesis, self.alpha as f64, self.confidence as f64) {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.stat = r.stat as f32;
+                    self.region = r.region;
+                    self.strings[6] = self.pval.to_string();
+                    self.strings[8] = self.stat.to_string();
+                    self.strings[9] = self.region.to_string();
+                    self.strings[10] = r.conclusion(self.alpha as f64);
+                    self.strings[13].clear();
+                }
+                Err(e) => self.strings[13] = e,
+            }
         }
         ui.num_box("", &mut self.strings[6].clone());
-        ui.label(&self.strings[7]);
+        ui.test_results(&self.strings[8], "", &self.region, &self.strings[10]);
+        ui.label(&self.strings[13]);
         resp
     }
 }
@@ -559,6 +948,15 @@ pub(crate) struct TOneStats {
     interval: Constr<f32>,
     hypothesis: Constr<f32>,
     pval: f32,
+    alpha: f32,
+    stat: f32,
+    df: String,
+    region: Constr<f32>,
+    conclusion: String,
+    solve: CiSolve,
+    margin: String,
+    raw: bool,
+    data: String,
 }
 
 impl Default for TOneStats {
@@ -567,6 +965,19 @@ impl Default for TOneStats {
             sample_mean: "0.0".parse().unwrap(),
             sample_dev: "1.0".parse().unwrap(),
             sample_size: 30,
+            confidence: 0.95,
+            interval: Constr::In(-1.96, 1.96),
+            hypothesis: Constr::NE(0.0),
+            pval: 0.05,
+            alpha: 0.05,
+            stat: 0.0,
+            df: "".to_string(),
+            region: Constr::None,
+            conclusion: "".to_string(),
+            solve: CiSolve::Interval,
+            margin: "".to_string(),
+            raw: false,
+            data: "".to_string(),
             strings: [
                 "0.0".to_string(),
                 "1.0".to_string(),
@@ -575,96 +986,171 @@ impl Default for TOneStats {
                 "[-1.96, 1.96]".to_string(),
                 "!=0.0".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "interval".to_string(),
+                "".to_string(),
+                "false".to_string(),
                 "".to_string(),
             ],
-            confidence: 0.95,
-            interval: Constr::In(-1.96, 1.96),
-            hypothesis: Constr::NENone,
-            pval: 0.05,
         }
     }
 }
 
-impl Widget for &mut TOneStats {
-    fn ui(self, ui: &mut Ui) -> egui::Response {
-        let mut resp = ui.num_box("mean", &mut self.strings[0]);
-        resp = resp.union(ui.num_box("sd", &mut self.strings[1]));
-        resp = resp.union(ui.num_box("sample size", &mut self.strings[2]));
-        resp = resp.union(ui.num_box("confidence", &mut self.strings[3]));
-        if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(mean) = self.sample_mean.eval() else {
-                    self.strings[7] = "Mean is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev) = self.sample_dev.eval() else {
-                    self.strings[7] = "Standard deviation is invalid".to_owned();
-                    return;
-                };
+impl TOneStats {
+    /// Fills the fields, taking the summaries from the data when using raw input.
+    fn fill(&mut self) -> Result<(), String> {
+        self.vfill();
+        if self.raw {
+            let (mean, sd, n) = summarize(&self.data)?;
+            self.sample_mean = expr(mean);
+            self.sample_dev = expr(sd);
+            self.sample_size = n;
+        }
+        Ok(())
+    }
 
-                let std_err = dev / (self.sample_size as f64).sqrt();
+    /// Sample mean and its standard error.
+    fn mean_se(&self) -> Result<(f64, f64), String> {
+        let mean = self.sample_mean.eval().map_err(|_| "Mean is invalid")?;
+        let dev = self
+            .sample_dev
+            .eval()
+            .map_err(|_| "Standard deviation is invalid")?;
+        let se = dev / (self.sample_size as f64).sqrt();
+        if se.is_nan() || se <= 0.0 {
+            return Err("Standard error must be positive".to_string());
+        }
+        Ok((mean, se))
+    }
+}
 
-                let Ok(n) = StudentsT::new(mean, std_err, self.sample_size as f64 - 1.0) else {
-                    self.strings[7] = "Not a valid T distr".to_string();
-                    return;
-                };
+impl HypothesisTest for TOneStats {
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let (mean, se) = self.mean_se()?;
+        Ok(((mean - h0) / se, NullDist::T(self.sample_size as f64 - 1.0)))
+    }
 
-                let int_l = n.inverse_cdf((1.0 - self.confidence as f64) / 2.0);
-                let int_h = n.inverse_cdf((1.0 + self.confidence as f64) / 2.0);
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String> {
+        let (mean, se) = self.mean_se()?;
+        let q =
+            NullDist::T(self.sample_size as f64 - 1.0).inverse_cdf((1.0 + confidence) / 2.0)? * se;
+        Ok(Constr::In((mean - q) as f32, (mean + q) as f32))
+    }
 
-                self.interval = Constr::In(int_l as f32, int_h as f32);
+    fn estimate(&self) -> Result<f64, String> {
+        Ok(self.mean_se()?.0)
+    }
+}
 
-                self.strings[4] = self.interval.to_string();
-                self.strings[7].clear();
-            };
-            f();
+impl Widget for &mut TOneStats {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.raw_input(&mut self.raw);
+        if resp.changed() {
+            self.strings[14] = self.raw.to_string();
+        }
+        if self.raw {
+            resp = resp.union(ui.data_box("Data", &mut self.strings[15]));
+            ui.num_box("mean", &mut expr_str(&self.sample_mean));
+            ui.num_box("sd", &mut expr_str(&self.sample_dev));
+        } else {
+            resp = resp.union(ui.num_box("mean", &mut self.strings[0]));
+            resp = resp.union(ui.num_box("sd", &mut self.strings[1]));
+        }
+        if ui.ci_solve(&mut self.solve, &mut self.interval) {
+            self.strings[12] = self.solve.to_string();
+            self.strings[4] = self.interval.to_string();
+            resp.mark_changed();
+        }
+        if self.raw && self.solve != CiSolve::N {
+            ui.num_box("sample size", &mut self.sample_size.to_string());
+        } else {
+            resp = resp.union(ui.solve_box(
+                "sample size",
+                &mut self.strings[2],
+                self.solve == CiSolve::N,
+            ));
+        }
+        resp = resp.union(ui.solve_box(
+            "confidence",
+            &mut self.strings[3],
+            self.solve == CiSolve::Confidence,
+        ));
+        resp = resp.union(ui.solve_box(
+            "interval",
+            &mut self.strings[4],
+            self.solve == CiSolve::Interval,
+        ));
+        if self.solve != CiSolve::Interval {
+            resp = resp.union(ui.num_box("or margin of error", &mut self.strings[13]));
         }
-        ui.num_box("", &mut self.strings[4].clone());
-        ui.label("Hypothesis");
-        resp = resp.union(ui.num_box("H1", &mut self.strings[5]));
         if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(mean) = self.sample_mean.eval() else {
-                    self.strings[7] = "Mean is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev) = self.sample_dev.eval() else {
-                    self.strings[7] = "Standard deviation is invalid".to_owned();
-                    return;
-                };
-
-                let std_err = dev / (self.sample_size as f64).sqrt();
-
-                let Ok(n) = StudentsT::new(mean, std_err, self.sample_size as f64 - 1.0) else {
-                    self.strings[7] = "Not a valid T distr".to_string();
-                    return;
-                };
-
-                self.pval = match self.hypothesis {
-                    Constr::GE(v) | Constr::GT(v) => n.cdf(v as f64),
-                    Constr::LE(v) | Constr::LT(v) => 1. - n.cdf(v as f64),
-                    Constr::NE(v) => {
-                        if mean > v as f64 {
-                            2.0 * n.cdf(v as f64)
-                        } else {
-                            2.0 - 2.0 * n.cdf(v as f64)
+            match self.fill().and_then(|_| {
+                solve_ci(
+                    &*self,
+                    self.solve,
+                    &self.interval,
+                    self.confidence as f64,
+                    &self.margin,
+                    |t, n| t.sample_size = n,
+                )
+            }) {
+                Ok((ci, c, n)) => {
+                    match self.solve {
+                        CiSolve::Interval => {
+                            self.interval = ci;
+                            self.strings[4] = ci.to_string();
                         }
+                        CiSolve::Confidence => {
+                            self.confidence = c as f32;
+                            self.strings[3] = fmt_num(c);
+                        }
+                        CiSolve::N => {}
                     }
-                    _ => {
-                        self.strings[7] = "Not valid hypothesis".to_owned();
-                        return;
+                    if let Some(n) = n {
+                        self.sample_size = n;
+                        self.strings[2] = n.to_string();
                     }
-                } as f32;
-
-                self.strings[6] = self.pval.to_string();
-                self.strings[7].clear();
-            };
-            f();
+                    self.strings[16].clear();
+                }
+                Err(e) => self.strings[16] = e,
+            }
+        }
+        ui.label("Hypothesis");
+        resp = resp.union(ui.num_box("H1", &mut self.strings[5]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[7]));
+        if resp.changed() {
+            self.strings[8].clear();
+            match self
+                .fill()
+                .and_then(|_| self.run(&self.hypothesis, self.alpha as f64, self.confidence as f64))
+            {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.stat = r.stat as f32;
+                    self.region = r.region;
+                    self.strings[6] = self.pval.to_string();
+                    self.strings[8] = self.stat.to_string();
+                    self.strings[9] = r.null.df();
+                    self.strings[10] = self.region.to_string();
+                    self.strings[11] = r.conclusion(self.alpha as f64);
+                    self.strings[16].clear();
+                }
+                Err(e) => self.strings[16] = e,
+            }
         }
         ui.num_box("", &mut self.strings[6].clone());
-        ui.label(&self.strings[7]);
+        ui.test_results(
+            &self.strings[8],
+            &self.strings[9],
+            &self.region,
+            &self.strings[11],
+        );
+        ui.label(&self.strings[16]);
         resp
     }
 }
@@ -682,6 +1168,13 @@ pub(crate) struct ZTwoStats {
     interval: Constr<f32>,
     hypothesis: Constr<f32>,
     pval: f32,
+    alpha: f32,
+    stat: f32,
+    region: Constr<f32>,
+    conclusion: String,
+    raw: bool,
+    data_1: String,
+    data_2: String,
 }
 
 impl Default for ZTwoStats {
@@ -695,6 +1188,15 @@ impl Default for ZTwoStats {
             sample_size_2: 30,
             confidence: 0.95,
             interval: Constr::None,
+            hypothesis: Constr::NE(0.0),
+            pval: 0.05,
+            alpha: 0.05,
+            stat: 0.0,
+            region: Constr::None,
+            conclusion: "".to_string(),
+            raw: false,
+            data_1: "".to_string(),
+            data_2: "".to_string(),
             strings: [
                 "0.0".to_string(),
                 "1.0".to_string(),
@@ -706,117 +1208,144 @@ impl Default for ZTwoStats {
                 "".to_string(),
                 "!=0.0".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "false".to_string(),
+                "".to_string(),
                 "".to_string(),
             ],
-            hypothesis: Constr::NE(0.0),
-            pval: 0.05,
         }
     }
 }
 
-impl Widget for &mut ZTwoStats {
-    fn ui(self, ui: &mut Ui) -> egui::Response {
-        let mut resp = ui.num_box("mean 1", &mut self.strings[0]);
-        resp = resp.union(ui.num_box("sd 1", &mut self.strings[1]));
-        resp = resp.union(ui.num_box("sample size 1", &mut self.strings[2]));
-        resp = resp.union(ui.num_box("mean 2", &mut self.strings[3]));
-        resp = resp.union(ui.num_box("sd 2", &mut self.strings[4]));
-        resp = resp.union(ui.num_box("sample size 2", &mut self.strings[5]));
-        resp = resp.union(ui.num_box("confidence", &mut self.strings[6]));
-        if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(mean1) = self.sample_mean_1.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev1) = self.sample_dev_1.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(mean2) = self.sample_mean_2.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev2) = self.sample_dev_2.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
+impl ZTwoStats {
+    /// Fills the fields, taking the means and sizes from the data when using raw input.
+    /// The sds stay typed, as a z test needs them known rather than estimated.
+    fn fill(&mut self) -> Result<(), String> {
+        self.vfill();
+        if self.raw {
+            let (mean, _, n) = summarize(&self.data_1).map_err(|e| format!("Sample 1: {e}"))?;
+            self.sample_mean_1 = expr(mean);
+            self.sample_size_1 = n;
+            let (mean, _, n) = summarize(&self.data_2).map_err(|e| format!("Sample 2: {e}"))?;
+            self.sample_mean_2 = expr(mean);
+            self.sample_size_2 = n;
+        }
+        Ok(())
+    }
 
-                let std_err = (dev1 * dev1 / self.sample_size_1 as f64
-                    + dev2 * dev2 / self.sample_size_2 as f64)
-                    .sqrt();
+    /// Difference of the sample means, its standard error.
+    fn diff_se(&self) -> Result<(f64, f64), String> {
+        let mean1 = self.sample_mean_1.eval().map_err(|_| "Mean 1 is invalid")?;
+        let dev1 = self
+            .sample_dev_1
+            .eval()
+            .map_err(|_| "Standard deviation 1 is invalid")?;
+        let mean2 = self.sample_mean_2.eval().map_err(|_| "Mean 2 is invalid")?;
+        let dev2 = self
+            .sample_dev_2
+            .eval()
+            .map_err(|_| "Standard deviation 2 is invalid")?;
+        let a = dev1 * dev1 / self.sample_size_1 as f64;
+        let b = dev2 * dev2 / self.sample_size_2 as f64;
+        let se = (a + b).sqrt();
+        if se.is_nan() || se <= 0.0 {
+            return Err("Standard error must be positive".to_string());
+        }
+        Ok((mean1 - mean2, se))
+    }
+}
 
-                let Ok(n) = Normal::new(mean1 - mean2, std_err) else {
-                    self.strings[10] = "Not a valid Normal distr".to_string();
-                    return;
-                };
+impl HypothesisTest for ZTwoStats {
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let (diff, se) = self.diff_se()?;
+        Ok(((diff - h0) / se, NullDist::Normal))
+    }
 
-                let int_l = n.inverse_cdf((1.0 - self.confidence as f64) / 2.0);
-                let int_h = n.inverse_cdf((1.0 + self.confidence as f64) / 2.0);
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String> {
+        let (diff, se) = self.diff_se()?;
+        let q = NullDist::Normal.inverse_cdf((1.0 + confidence) / 2.0)? * se;
+        Ok(Constr::In((diff - q) as f32, (diff + q) as f32))
+    }
 
-                self.interval = Constr::In(int_l as f32, int_h as f32);
+    fn estimate(&self) -> Result<f64, String> {
+        Ok(self.diff_se()?.0)
+    }
+}
 
-                self.strings[7] = self.interval.to_string();
-                self.strings[10].clear();
-            };
-            f();
-        }
-        ui.num_box("", &mut self.strings[7].clone());
+impl Widget for &mut ZTwoStats {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.raw_input(&mut self.raw);
+        if resp.changed() {
+            self.strings[14] = self.raw.to_string();
+        }
+        if self.raw {
+            resp = resp.union(
+                ui.horizontal(|ui| {
+                    let resp = ui.data_box("Sample 1", &mut self.strings[15]);
+                    resp.union(ui.data_box("Sample 2", &mut self.strings[16]))
+                })
+                .inner,
+            );
+            ui.num_box("mean 1", &mut expr_str(&self.sample_mean_1));
+            resp = resp.union(ui.num_box("sd 1", &mut self.strings[1]));
+            ui.num_box("sample size 1", &mut self.sample_size_1.to_string());
+            ui.num_box("mean 2", &mut expr_str(&self.sample_mean_2));
+            resp = resp.union(ui.num_box("sd 2", &mut self.strings[4]));
+            ui.num_box("sample size 2", &mut self.sample_size_2.to_string());
+        } else {
+            resp = resp.union(ui.num_box("mean 1", &mut self.strings[0]));
+            resp = resp.union(ui.num_box("sd 1", &mut self.strings[1]));
+            resp = resp.union(ui.num_box("sample size 1", &mut self.strings[2]));
+            resp = resp.union(ui.num_box("mean 2", &mut self.strings[3]));
+            resp = resp.union(ui.num_box("sd 2", &mut self.strings[4]));
+            resp = resp.union(ui.num_box("sample size 2", &mut self.strings[5]));
+        }
+        resp = resp.union(ui.num_box("confidence", &mut self.strings[6]));
+        if resp.changed() {
+            match self
+                .fill()
+                .and_then(|_| self.interval(self.confidence as f64))
+            {
+                Ok(ci) => {
+                    self.interval = ci;
+                    self.strings[7] = self.interval.to_string();
+                    self.strings[17].clear();
+                }
+                Err(e) => self.strings[17] = e,
+            }
+        }
+        ui.num_box("", &mut self.strings[7].clone());
         ui.label("Hypothesis");
         resp = resp.union(ui.num_box("H1", &mut self.strings[8]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[10]));
         if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(mean1) = self.sample_mean_1.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev1) = self.sample_dev_1.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(mean2) = self.sample_mean_2.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev2) = self.sample_dev_2.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
-
-                let std_err = (dev1 * dev1 / self.sample_size_1 as f64
-                    + dev2 * dev2 / self.sample_size_2 as f64)
-                    .sqrt();
-
-                let Ok(n) = Normal::new(mean1 - mean2, std_err) else {
-                    self.strings[10] = "Not a valid Normal distr".to_string();
-                    return;
-                };
-
-                self.pval = match self.hypothesis {
-                    Constr::GE(v) | Constr::GT(v) => n.cdf(v as f64),
-                    Constr::LE(v) | Constr::LT(v) => 1. - n.cdf(v as f64),
-                    Constr::NE(v) => {
-                        if mean1 - mean2 > v as f64 {
-                            2.0 * n.cdf(v as f64)
-                        } else {
-                            2.0 - 2.0 * n.cdf(v as f64)
-                        }
-                    }
-                    _ => {
-                        self.strings[10] = "Not valid hypothesis".to_owned();
-                        return;
-                    }
-                } as f32;
-
-                self.strings[9] = self.pval.to_string();
-                self.strings[10].clear();
-            };
-            f();
+            self.strings[11].clear();
+            match self
+                .fill()
+                .and_then(|_| self.run(&self.hypothesis, self.alpha as f64, self.confidence as f64))
+            {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.stat = r.stat as f32;
+                    self.region = r.region;
+                    self.interval = r.ci;
+                    self.strings[7] = self.interval.to_string();
+                    self.strings[9] = self.pval.to_string();
+                    self.strings[11] = self.stat.to_string();
+                    self.strings[12] = self.region.to_string();
+                    self.strings[13] = r.conclusion(self.alpha as f64);
+                    self.strings[17].clear();
+                }
+                Err(e) => self.strings[17] = e,
+            }
         }
         ui.num_box("", &mut self.strings[9].clone());
-        ui.label(&self.strings[10]);
+        ui.test_results(&self.strings[11], "", &self.region, &self.strings[13]);
+        ui.label(&self.strings[17]);
         resp
     }
 }
@@ -834,6 +1363,14 @@ pub(crate) struct TTwoStats {
     interval: Constr<f32>,
     hypothesis: Constr<f32>,
     pval: f32,
+    alpha: f32,
+    stat: f32,
+    df: String,
+    region: Constr<f32>,
+    conclusion: String,
+    raw: bool,
+    data_1: String,
+    data_2: String,
 }
 
 impl Default for TTwoStats {
@@ -847,6 +1384,16 @@ impl Default for TTwoStats {
             sample_size_2: 30,
             confidence: 0.95,
             interval: Constr::None,
+            hypothesis: Constr::NE(0.0),
+            pval: 0.05,
+            alpha: 0.05,
+            stat: 0.0,
+            df: "".to_string(),
+            region: Constr::None,
+            conclusion: "".to_string(),
+            raw: false,
+            data_1: "".to_string(),
+            data_2: "".to_string(),
             strings: [
                 "0.0".to_string(),
                 "1.0".to_string(),
@@ -858,127 +1405,159 @@ impl Default for TTwoStats {
                 "".to_string(),
                 "!=0.0".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "false".to_string(),
+                "".to_string(),
                 "".to_string(),
             ],
-            hypothesis: Constr::NE(0.0),
-            pval: 0.05,
         }
     }
 }
 
-impl Widget for &mut TTwoStats {
-    fn ui(self, ui: &mut Ui) -> egui::Response {
-        let mut resp = ui.num_box("mean 1", &mut self.strings[0]);
-        resp = resp.union(ui.num_box("sd 1", &mut self.strings[1]));
-        resp = resp.union(ui.num_box("sample size 1", &mut self.strings[2]));
-        resp = resp.union(ui.num_box("mean 2", &mut self.strings[3]));
-        resp = resp.union(ui.num_box("sd 2", &mut self.strings[4]));
-        resp = resp.union(ui.num_box("sample size 2", &mut self.strings[5]));
-        resp = resp.union(ui.num_box("confidence", &mut self.strings[6]));
-        if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(mean1) = self.sample_mean_1.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev1) = self.sample_dev_1.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(mean2) = self.sample_mean_2.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev2) = self.sample_dev_2.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
+impl TTwoStats {
+    /// Fills the fields, taking the summaries from the data when using raw input.
+    fn fill(&mut self) -> Result<(), String> {
+        self.vfill();
+        if self.raw {
+            let (mean, sd, n) = summarize(&self.data_1).map_err(|e| format!("Sample 1: {e}"))?;
+            self.sample_mean_1 = expr(mean);
+            self.sample_dev_1 = expr(sd);
+            self.sample_size_1 = n;
+            let (mean, sd, n) = summarize(&self.data_2).map_err(|e| format!("Sample 2: {e}"))?;
+            self.sample_mean_2 = expr(mean);
+            self.sample_dev_2 = expr(sd);
+            self.sample_size_2 = n;
+        }
+        Ok(())
+    }
 
-                let std_err = (dev1 * dev1 / self.sample_size_1 as f64
-                    + dev2 * dev2 / self.sample_size_2 as f64)
-                    .sqrt();
-                let a = dev1 * dev1 / self.sample_size_1 as f64;
-                let b = dev2 * dev2 / self.sample_size_2 as f64;
-                let df = (a + b) * (a + b)
-                    / (a * a / (self.sample_size_1 as f64 - 1.0)
-                        + b * b / (self.sample_size_2 as f64 - 1.0));
-
-                let Ok(n) = StudentsT::new(mean1 - mean2, std_err, df) else {
-                    self.strings[10] = "Not a valid T distr".to_string();
-                    return;
-                };
+    /// Difference of the sample means, its standard error and Welch's df.
+    fn diff_se(&self) -> Result<(f64, f64, f64), String> {
+        let mean1 = self.sample_mean_1.eval().map_err(|_| "Mean 1 is invalid")?;
+        let dev1 = self
+            .sample_dev_1
+            .eval()
+            .map_err(|_| "Standard deviation 1 is invalid")?;
+        let mean2 = self.sample_mean_2.eval().map_err(|_| "Mean 2 is invalid")?;
+        let dev2 = self
+            .sample_dev_2
+            .eval()
+            .map_err(|_| "Standard deviation 2 is invalid")?;
+        let a = dev1 * dev1 / self.sample_size_1 as f64;
+        let b = dev2 * dev2 / self.sample_size_2 as f64;
+        let se = (a + b).sqrt();
+        if se.is_nan() || se <= 0.0 {
+            return Err("Standard error must be positive".to_string());
+        }
+        let df = (a + b) * (a + b)
+            / (a * a / (self.sample_size_1 as f64 - 1.0)
+                + b * b / (self.sample_size_2 as f64 - 1.0));
+        Ok((mean1 - mean2, se, df))
+    }
+}
+
+impl HypothesisTest for TTwoStats {
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let (diff, se, df) = self.diff_se()?;
+        Ok(((diff - h0) / se, NullDist::T(df)))
+    }
 
-                let int_l = n.inverse_cdf((1.0 - self.confidence as f64) / 2.0);
-                let int_h = n.inverse_cdf((1.0 + self.confidence as f64) / 2.0);
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String> {
+        let (diff, se, df) = self.diff_se()?;
+        let q = NullDist::T(df).inverse_cdf((1.0 + confidence) / 2.0)? * se;
+        Ok(Constr::In((diff - q) as f32, (diff + q) as f32))
+    }
 
-                self.interval = Constr::In(int_l as f32, int_h as f32);
+    fn estimate(&self) -> Result<f64, String> {
+        Ok(self.diff_se()?.0)
+    }
+}
 
-                self.strings[7] = self.interval.to_string();
-                self.strings[10].clear();
-            };
-            f();
+impl Widget for &mut TTwoStats {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.raw_input(&mut self.raw);
+        if resp.changed() {
+            self.strings[15] = self.raw.to_string();
+        }
+        if self.raw {
+            resp = resp.union(
+                ui.horizontal(|ui| {
+                    let resp = ui.data_box("Sample 1", &mut self.strings[16]);
+                    resp.union(ui.data_box("Sample 2", &mut self.strings[17]))
+                })
+                .inner,
+            );
+            for (l, mut v) in [
+                ("mean 1", expr_str(&self.sample_mean_1)),
+                ("sd 1", expr_str(&self.sample_dev_1)),
+                ("sample size 1", self.sample_size_1.to_string()),
+                ("mean 2", expr_str(&self.sample_mean_2)),
+                ("sd 2", expr_str(&self.sample_dev_2)),
+                ("sample size 2", self.sample_size_2.to_string()),
+            ] {
+                ui.num_box(l, &mut v);
+            }
+        } else {
+            resp = resp.union(ui.num_box("mean 1", &mut self.strings[0]));
+            resp = resp.union(ui.num_box("sd 1", &mut self.strings[1]));
+            resp = resp.union(ui.num_box("sample size 1", &mut self.strings[2]));
+            resp = resp.union(ui.num_box("mean 2", &mut self.strings[3]));
+            resp = resp.union(ui.num_box("sd 2", &mut self.strings[4]));
+            resp = resp.union(ui.num_box("sample size 2", &mut self.strings[5]));
+        }
+        resp = resp.union(ui.num_box("confidence", &mut self.strings[6]));
+        if resp.changed() {
+            match self
+                .fill()
+                .and_then(|_| self.interval(self.confidence as f64))
+            {
+                Ok(ci) => {
+                    self.interval = ci;
+                    self.strings[7] = self.interval.to_string();
+                    self.strings[18].clear();
+                }
+                Err(e) => self.strings[18] = e,
+            }
         }
         ui.num_box("", &mut self.strings[7].clone());
         ui.label("Hypothesis");
         resp = resp.union(ui.num_box("H1", &mut self.strings[8]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[10]));
         if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(mean1) = self.sample_mean_1.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev1) = self.sample_dev_1.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(mean2) = self.sample_mean_2.eval() else {
-                    self.strings[10] = "Mean 1 is invalid".to_owned();
-                    return;
-                };
-                let Ok(dev2) = self.sample_dev_2.eval() else {
-                    self.strings[10] = "Standard deviation 1 is invalid".to_owned();
-                    return;
-                };
-
-                let std_err = (dev1 * dev1 / self.sample_size_1 as f64
-                    + dev2 * dev2 / self.sample_size_2 as f64)
-                    .sqrt();
-                let a = dev1 * dev1 / self.sample_size_1 as f64;
-                let b = dev2 * dev2 / self.sample_size_2 as f64;
-                let df = (a + b) * (a + b)
-                    / (a * a / (self.sample_size_1 as f64 - 1.0)
-                        + b * b / (self.sample_size_2 as f64 - 1.0));
-
-                let Ok(n) = StudentsT::new(mean1 - mean2, std_err, df) else {
-                    self.strings[10] = "Not a valid Normal distr".to_string();
-                    return;
-                };
-
-                self.pval = match self.hypothesis {
-                    Constr::GE(v) | Constr::GT(v) => n.cdf(v as f64),
-                    Constr::LE(v) | Constr::LT(v) => 1. - n.cdf(v as f64),
-                    Constr::NE(v) => {
-                        if mean1 - mean2 > v as f64 {
-                            2.0 * n.cdf(v as f64)
-                        } else {
-                            2.0 - 2.0 * n.cdf(v as f64)
-                        }
-                    }
-                    _ => {
-                        self.strings[10] = "Not valid hypothesis".to_owned();
-                        return;
-                    }
-                } as f32;
-
-                self.strings[9] = self.pval.to_string();
-                self.strings[10].clear();
-            };
-            f();
+            self.strings[11].clear();
+            match self
+                .fill()
+                .and_then(|_| self.run(&self.hypothesis, self.alpha as f64, self.confidence as f64))
+            {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.stat = r.stat as f32;
+                    self.region = r.region;
+                    self.interval = r.ci;
+                    self.strings[7] = self.interval.to_string();
+                    self.strings[9] = self.pval.to_string();
+                    self.strings[11] = self.stat.to_string();
+                    self.strings[12] = r.null.df();
+                    self.strings[13] = self.region.to_string();
+                    self.strings[14] = r.conclusion(self.alpha as f64);
+                    self.strings[18].clear();
+                }
+                Err(e) => self.strings[18] = e,
+            }
         }
         ui.num_box("", &mut self.strings[9].clone());
-        ui.label(&self.strings[10]);
+        ui.test_results(
+            &self.strings[11],
+            &self.strings[12],
+            &self.region,
+            &self.strings[14],
+        );
+        ui.label(&self.strings[18]);
         resp
     }
 }
@@ -993,6 +1572,13 @@ pub(crate) struct VarOneStats {
     intervaldev: Constr<f32>,
     hypothesis: Constr<f32>,
     pval: f32,
+    alpha: f32,
+    stat: f32,
+    df: String,
+    region: Constr<f32>,
+    conclusion: String,
+    raw: bool,
+    data: String,
 }
 
 impl Default for VarOneStats {
@@ -1000,6 +1586,18 @@ impl Default for VarOneStats {
         Self {
             sample_dev: "1.0".parse().unwrap(),
             sample_size: 30,
+            confidence: 0.95,
+            intervalvar: Constr::In(0.63, 1.81),
+            intervaldev: Constr::In(0.80, 1.34),
+            hypothesis: Constr::NE(1.0),
+            pval: 0.05,
+            alpha: 0.05,
+            stat: 0.0,
+            df: "".to_string(),
+            region: Constr::None,
+            conclusion: "".to_string(),
+            raw: false,
+            data: "".to_string(),
             strings: [
                 "1.0".to_string(),
                 "30".to_string(),
@@ -1008,93 +1606,134 @@ impl Default for VarOneStats {
                 "[0.80, 1.34]".to_string(),
                 "!=1".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "false".to_string(),
                 "".to_string(),
             ],
-            confidence: 0.95,
-            intervalvar: Constr::In(0.63, 1.81),
-            intervaldev: Constr::In(0.80, 1.34),
-            hypothesis: Constr::NE(1.0),
-            pval: 0.05,
         }
     }
 }
 
-impl Widget for &mut VarOneStats {
-    fn ui(self, ui: &mut Ui) -> egui::Response {
-        let mut resp = ui.num_box("sd", &mut self.strings[0]);
-        resp = resp.union(ui.num_box("sample size", &mut self.strings[1]));
-        resp = resp.union(ui.num_box("confidence", &mut self.strings[2]));
-        if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(dev) = self.sample_dev.eval() else {
-                self.strings[7] = "Standard deviation is invalid".to_owned();
-                return;
-            };
-                let dev = dev as f32;
+impl VarOneStats {
+    /// Fills the fields, taking the summaries from the data when using raw input.
+    fn fill(&mut self) -> Result<(), String> {
+        self.vfill();
+        if self.raw {
+            let (_, sd, n) = summarize(&self.data)?;
+            self.sample_dev = expr(sd);
+            self.sample_size = n;
+        }
+        Ok(())
+    }
 
-                let free = (self.sample_size - 1) as f32;
-                let Ok(n) = ChiSquared::new(free as f64) else {
-                self.strings[7] = "Not a valid Chi Squared distr".to_string();
-                return;
-            };
+    fn dev(&self) -> Result<f64, String> {
+        let dev = self
+            .sample_dev
+            .eval()
+            .map_err(|_| "Standard deviation is invalid")?;
+        if self.sample_size < 2 {
+            return Err("Sample size must be at least 2".to_string());
+        }
+        Ok(dev)
+    }
+}
 
-                let int_l = n.inverse_cdf((1.0 - self.confidence as f64) / 2.0) as f32;
-                let int_h = n.inverse_cdf((1.0 + self.confidence as f64) / 2.0) as f32;
+impl HypothesisTest for VarOneStats {
+    /// Tests the standard deviation with (n - 1) s^2 / sd^2.
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let dev = self.dev()?;
+        let free = (self.sample_size - 1) as f64;
+        Ok((free * dev * dev / (h0 * h0), NullDist::ChiSquared(free)))
+    }
 
-                let err = free * dev * dev;
-                self.intervalvar = Constr::In(err / int_h, err / int_l);
-                self.intervaldev = Constr::In((err / int_h).sqrt(), (err / int_l).sqrt());
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String> {
+        let dev = self.dev()?;
+        let free = (self.sample_size - 1) as f64;
+        let null = NullDist::ChiSquared(free);
+        let int_l = null.inverse_cdf((1.0 - confidence) / 2.0)?;
+        let int_h = null.inverse_cdf((1.0 + confidence) / 2.0)?;
+        let err = free * dev * dev;
+        Ok(Constr::In(
+            (err / int_h).sqrt() as f32,
+            (err / int_l).sqrt() as f32,
+        ))
+    }
 
-                self.strings[3] = self.intervalvar.to_string();
-                self.strings[4] = self.intervaldev.to_string();
-                self.strings[7].clear();
-            };
-            f();
+    fn estimate(&self) -> Result<f64, String> {
+        self.dev()
+    }
+}
+
+impl Widget for &mut VarOneStats {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.raw_input(&mut self.raw);
+        if resp.changed() {
+            self.strings[12] = self.raw.to_string();
+        }
+        if self.raw {
+            resp = resp.union(ui.data_box("Data", &mut self.strings[13]));
+            ui.num_box("sd", &mut expr_str(&self.sample_dev));
+            ui.num_box("sample size", &mut self.sample_size.to_string());
+        } else {
+            resp = resp.union(ui.num_box("sd", &mut self.strings[0]));
+            resp = resp.union(ui.num_box("sample size", &mut self.strings[1]));
+        }
+        resp = resp.union(ui.num_box("confidence", &mut self.strings[2]));
+        if resp.changed() {
+            match self
+                .fill()
+                .and_then(|_| self.interval(self.confidence as f64))
+            {
+                Ok(ci) => {
+                    if let Constr::In(a, b) = ci {
+                        self.intervalvar = Constr::In(a * a, b * b);
+                    }
+                    self.intervaldev = ci;
+                    self.strings[3] = self.intervalvar.to_string();
+                    self.strings[4] = self.intervaldev.to_string();
+                    self.strings[14].clear();
+                }
+                Err(e) => self.strings[14] = e,
+            }
         }
         ui.num_box("Var", &mut self.strings[3].clone());
         ui.num_box("SD ", &mut self.strings[4].clone());
         ui.label("Hypothesis");
         resp = resp.union(ui.num_box("H1: sd", &mut self.strings[5]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[7]));
         if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(dev) = self.sample_dev.eval() else {
-                    self.strings[7] = "Standard deviation is invalid".to_owned();
-                    return;
-                };
-
-                let free = (self.sample_size - 1) as f32;
-                let Ok(n) = ChiSquared::new(free as f64) else {
-                    self.strings[7] = "Not a valid Chi Squared distr".to_string();
-                    return;
-                };
-
-                let err = free * (dev * dev) as f32;
-
-                self.pval = match self.hypothesis {
-                    Constr::GE(v) | Constr::GT(v) => 1. - n.cdf((err / v / v) as f64),
-                    Constr::LE(v) | Constr::LT(v) => n.cdf((err / v / v) as f64),
-                    Constr::NE(v) => {
-                        if dev < v as f64 {
-                            2.0 * n.cdf((err / v / v) as f64)
-                        } else {
-                            2.0 - 2.0 * n.cdf((err / v / v) as f64)
-                        }
-                    }
-                    _ => {
-                        self.strings[7] = "Not valid hypothesis".to_owned();
-                        return;
-                    }
-                } as f32;
-
-                self.strings[6] = self.pval.to_string();
-                self.strings[7].clear();
-            };
-            f();
+            self.strings[8].clear();
+            match self
+                .fill()
+                .and_then(|_| self.run(&self.hypothesis, self.alpha as f64, self.confidence as f64))
+            {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.stat = r.stat as f32;
+                    self.region = r.region;
+                    self.strings[6] = self.pval.to_string();
+                    self.strings[8] = self.stat.to_string();
+                    self.strings[9] = r.null.df();
+                    self.strings[10] = self.region.to_string();
+                    self.strings[11] = r.conclusion(self.alpha as f64);
+                    self.strings[14].clear();
+                }
+                Err(e) => self.strings[14] = e,
+            }
         }
         ui.num_box("", &mut self.strings[6].clone());
-        ui.label(&self.strings[7]);
+        ui.test_results(
+            &self.strings[8],
+            &self.strings[9],
+            &self.region,
+            &self.strings[11],
+        );
+        ui.label(&self.strings[14]);
         resp
     }
 }
@@ -1111,6 +1750,13 @@ pub(crate) struct VarTwoStats {
     intervaldev: Constr<f32>,
     hypothesis: Constr<f32>,
     pval: f32,
+    alpha: f32,
+    stat: f32,
+    df: String,
+    region: Constr<f32>,
+    conclusion: String,
+    solve: CiSolve,
+    margin: String,
 }
 
 impl Default for VarTwoStats {
@@ -1120,6 +1766,18 @@ impl Default for VarTwoStats {
             sample_size_1: 30,
             sample_dev_2: "1.2".parse().unwrap(),
             sample_size_2: 30,
+            confidence: 0.95,
+            intervalvar: Constr::In(0.33, 1.46),
+            intervaldev: Constr::In(0.57, 1.21),
+            hypothesis: Constr::NE(1.0),
+            pval: 0.05,
+            alpha: 0.05,
+            stat: 0.0,
+            df: "".to_string(),
+            region: Constr::None,
+            conclusion: "".to_string(),
+            solve: CiSolve::Interval,
+            margin: "".to_string(),
             strings: [
                 "1.0".to_string(),
                 "30".to_string(),
@@ -1130,108 +1788,156 @@ impl Default for VarTwoStats {
                 "[0.57, 1.21]".to_string(),
                 "!=1".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "interval".to_string(),
                 "".to_string(),
             ],
-            confidence: 0.95,
-            intervalvar: Constr::In(0.63, 1.81),
-            intervaldev: Constr::In(0.80, 1.34),
-            hypothesis: Constr::NE(1.0),
-            pval: 0.05,
         }
     }
 }
 
-impl Widget for &mut VarTwoStats {
-    fn ui(self, ui: &mut Ui) -> egui::Response {
-        let mut resp = ui.num_box("sd 1", &mut self.strings[0]);
-        resp = resp.union(ui.num_box("sample size 1", &mut self.strings[1]));
-        resp = resp.union(ui.num_box("sd 2", &mut self.strings[2]));
-        resp = resp.union(ui.num_box("sample size 2", &mut self.strings[3]));
-        resp = resp.union(ui.num_box("confidence", &mut self.strings[4]));
-        if resp.changed() {
-            self.vfill();
-            let mut f = || {
-                let Ok(dev1) = self.sample_dev_1.eval() else {
-                self.strings[9] = "Standard deviation 1 is invalid".to_owned();
-                return;
-            };
-                let dev1 = dev1 as f32;
-
-                let Ok(dev2) = self.sample_dev_2.eval() else {
-                self.strings[9] = "Standard deviation 2 is invalid".to_owned();
-                return;
-            };
-                let dev2 = dev2 as f32;
+impl VarTwoStats {
+    /// Ratio of the sample variances and its null distribution.
+    fn ratio(&self) -> Result<(f64, NullDist), String> {
+        let dev1 = self
+            .sample_dev_1
+            .eval()
+            .map_err(|_| "Standard deviation 1 is invalid")?;
+        let dev2 = self
+            .sample_dev_2
+            .eval()
+            .map_err(|_| "Standard deviation 2 is invalid")?;
+        if self.sample_size_1 < 2 || self.sample_size_2 < 2 {
+            return Err("Sample sizes must be at least 2".to_string());
+        }
+        let free1 = (self.sample_size_1 - 1) as f64;
+        let free2 = (self.sample_size_2 - 1) as f64;
+        Ok((dev1 * dev1 / dev2 / dev2, NullDist::F(free1, free2)))
+    }
+}
 
-                let free1 = (self.sample_size_1 - 1) as f64;
-                let free2 = (self.sample_size_2 - 1) as f64;
-                let Ok(n) = FisherSnedecor::new(free1, free2) else {
-                self.strings[9] = "Not a valid F distr".to_string();
-                return;
-            };
+impl HypothesisTest for VarTwoStats {
+    /// Tests sd1/sd2 with (s1^2 / s2^2) / ratio^2.
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let (err, null) = self.ratio()?;
+        Ok((err / (h0 * h0), null))
+    }
 
-                let int_l = n.inverse_cdf((1.0 - self.confidence as f64) / 2.0) as f32;
-                let int_h = n.inverse_cdf((1.0 + self.confidence as f64) / 2.0) as f32;
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String> {
+        let (err, null) = self.ratio()?;
+        let int_l = null.inverse_cdf((1.0 - confidence) / 2.0)?;
+        let int_h = null.inverse_cdf((1.0 + confidence) / 2.0)?;
+        Ok(Constr::In(
+            (err / int_h).sqrt() as f32,
+            (err / int_l).sqrt() as f32,
+        ))
+    }
 
-                let err = dev1 * dev1 / dev2 / dev2;
-                self.intervalvar = Constr::In(err / int_h, err / int_l);
-                self.intervaldev = Constr::In((err / int_h).sqrt(), (err / int_l).sqrt());
+    fn estimate(&self) -> Result<f64, String> {
+        Ok(self.ratio()?.0.sqrt())
+    }
+}
 
-                self.strings[5] = self.intervalvar.to_string();
-                self.strings[6] = self.intervaldev.to_string();
-                self.strings[9].clear();
-            };
-            f();
+impl Widget for &mut VarTwoStats {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.num_box("sd 1", &mut self.strings[0]);
+        if ui.ci_solve(&mut self.solve, &mut self.intervaldev) {
+            self.strings[14] = self.solve.to_string();
+            self.strings[6] = self.intervaldev.to_string();
+            resp.mark_changed();
         }
+        let n_solved = self.solve == CiSolve::N;
+        resp = resp.union(ui.solve_box("sample size 1", &mut self.strings[1], n_solved));
+        resp = resp.union(ui.num_box("sd 2", &mut self.strings[2]));
+        resp = resp.union(ui.solve_box("sample size 2", &mut self.strings[3], n_solved));
+        resp = resp.union(ui.solve_box(
+            "confidence",
+            &mut self.strings[4],
+            self.solve == CiSolve::Confidence,
+        ));
         ui.num_box("Var", &mut self.strings[5].clone());
-        ui.num_box("SD ", &mut self.strings[6].clone());
-        ui.label("Hypothesis");
-        resp = resp.union(ui.num_box("H1 sd1/sd2", &mut self.strings[7]));
+        resp =
+            resp.union(ui.solve_box("SD ", &mut self.strings[6], self.solve == CiSolve::Interval));
+        if self.solve != CiSolve::Interval {
+            resp = resp.union(ui.num_box("or margin of error", &mut self.strings[15]));
+            // The sd ratio interval is skewed, so these don't read like a symmetric interval's
+            ui.label("A two sided margin is half the width of the SD interval");
+            if n_solved {
+                ui.label("Both samples take the solved sample size");
+            }
+        }
         if resp.changed() {
             self.vfill();
-            let mut f = || {
-                let Ok(dev1) = self.sample_dev_1.eval() else {
-                self.strings[9] = "Standard deviation 1 is invalid".to_owned();
-                return;
-            };
-
-                let Ok(dev2) = self.sample_dev_2.eval() else {
-                self.strings[9] = "Standard deviation 2 is invalid".to_owned();
-                return;
-            };
-
-                let free1 = (self.sample_size_1 - 1) as f64;
-                let free2 = (self.sample_size_2 - 1) as f64;
-                let Ok(n) = FisherSnedecor::new(free1, free2) else {
-                self.strings[9] = "Not a valid F distr".to_string();
-                return;
-            };
-
-                let err = dev1 * dev1 / dev2 / dev2;
-
-                self.pval = match self.hypothesis {
-                    Constr::GE(v) | Constr::GT(v) => 1. - n.cdf(err / (v * v) as f64),
-                    Constr::LE(v) | Constr::LT(v) => n.cdf(err / (v * v) as f64),
-                    Constr::NE(v) => {
-                        if dev1 / dev2 < v as f64 {
-                            2.0 * n.cdf(err / (v * v) as f64)
-                        } else {
-                            2.0 - 2.0 * n.cdf(err / (v * v) as f64)
+            match solve_ci(
+                &*self,
+                self.solve,
+                &self.intervaldev,
+                self.confidence as f64,
+                &self.margin,
+                |t, n| {
+                    t.sample_size_1 = n;
+                    t.sample_size_2 = n;
+                },
+            ) {
+                Ok((ci, c, n)) => {
+                    self.intervalvar = squared(ci);
+                    self.strings[5] = self.intervalvar.to_string();
+                    match self.solve {
+                        CiSolve::Interval => {
+                            self.intervaldev = ci;
+                            self.strings[6] = ci.to_string();
+                        }
+                        CiSolve::Confidence => {
+                            self.confidence = c as f32;
+                            self.strings[4] = fmt_num(c);
                         }
+                        CiSolve::N => {}
                     }
-                    _ => {
-                        self.strings[9] = "Not valid hypothesis".to_owned();
-                        return;
+                    if let Some(n) = n {
+                        self.sample_size_1 = n;
+                        self.sample_size_2 = n;
+                        self.strings[1] = n.to_string();
+                        self.strings[3] = n.to_string();
                     }
-                } as f32;
-
-                self.strings[8] = self.pval.to_string();
-                self.strings[9].clear();
-            };
-            f();
+                    self.strings[16].clear();
+                }
+                Err(e) => self.strings[16] = e,
+            }
+        }
+        ui.label("Hypothesis");
+        resp = resp.union(ui.num_box("H1 sd1/sd2", &mut self.strings[7]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[9]));
+        if resp.changed() {
+            self.vfill();
+            self.strings[10].clear();
+            match self.run(&self.hypothesis, self.alpha as f64, self.confidence as f64) {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.stat = r.stat as f32;
+                    self.region = r.region;
+                    self.strings[8] = self.pval.to_string();
+                    self.strings[10] = self.stat.to_string();
+                    self.strings[11] = r.null.df();
+                    self.strings[12] = self.region.to_string();
+                    self.strings[13] = r.conclusion(self.alpha as f64);
+                    self.strings[16].clear();
+                }
+                Err(e) => self.strings[16] = e,
+            }
         }
         ui.num_box("", &mut self.strings[8].clone());
-        ui.label(&self.strings[9]);
+        ui.test_results(
+            &self.strings[10],
+            &self.strings[11],
+            &self.region,
+            &self.strings[13],
+        );
+        ui.label(&self.strings[16]);
         resp
     }
 }
@@ -1242,10 +1948,12 @@ pub(crate) struct KStats {
     sample_devs: Vec<Expr>,
     sample_sizes: Vec<usize>,
     kstrings: Vec<(String, String, String)>,
-    pool_avg: f32,
     hypothesis: Constr<f32>,
     pval: f32,
-    strings: [String; 4],
+    alpha: f32,
+    region: Constr<f32>,
+    /// pool, hypothesis, p, error, alpha, statistic, df, rejection region, conclusion
+    strings: [String; 9],
 }
 
 impl Default for KStats {
@@ -1254,7 +1962,6 @@ impl Default for KStats {
             sample_means: vec!["0.0".parse().unwrap()],
             sample_devs: vec!["1.0".parse().unwrap()],
             sample_sizes: vec![30],
-            pool_avg: 0.0,
             pval: 0.05,
             kstrings: vec![("0.0".to_string(), "1.0".to_string(), "30".to_string())],
             strings: [
@@ -1262,12 +1969,57 @@ impl Default for KStats {
                 "!=".to_string(),
                 "".to_string(),
                 "".to_string(),
+                "0.05".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
             ],
             hypothesis: Constr::NENone,
+            alpha: 0.05,
+            region: Constr::None,
         }
     }
 }
 
+impl HypothesisTest for KStats {
+    /// Distance of the means from h0, or from their pooled mean when no value is hypothesized.
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String> {
+        let means = self
+            .sample_means
+            .iter()
+            .map(|x| x.eval())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "Mean is invalid")?;
+        let devs = self
+            .sample_devs
+            .iter()
+            .map(|x| x.eval())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "Standard deviation is invalid")?;
+        let (center, free) = match self.hypothesis {
+            Constr::NE(_) => (h0, means.len()),
+            Constr::NENone => (self.pooled(&means), means.len() - 1),
+            _ => return Err("Not a valid hypothesis".to_string()),
+        };
+        let stat = means
+            .iter()
+            .zip(devs.iter())
+            .zip(self.sample_sizes.iter())
+            .map(|((m, d), s)| (m - center).powi(2) / (d * d / (*s as f64)))
+            .sum();
+        Ok((stat, NullDist::ChiSquared(free as f64)))
+    }
+
+    fn interval(&self, _confidence: f64) -> Result<Constr<f32>, String> {
+        Ok(Constr::None)
+    }
+
+    fn estimate(&self) -> Result<f64, String> {
+        Err("No single parameter is tested".to_string())
+    }
+}
+
 impl Widget for &mut KStats {
     fn ui(self, ui: &mut Ui) -> egui::Response {
         let rba = ui.button("Add sample");
@@ -1293,87 +2045,62 @@ impl Widget for &mut KStats {
         }
         ui.num_box("Pool", &mut self.strings[0].clone());
         resp = resp.union(ui.num_box("Hypothesis", &mut self.strings[1]));
+        resp = resp.union(ui.num_box("α", &mut self.strings[4]));
         if resp.changed() {
+            self.strings[5].clear();
             self.vfill();
-            let mut f = || {
-                let means: Vec<_> = self
-                    .sample_means
-                    .iter()
-                    .filter_map(|x| x.eval().ok())
-                    .collect();
-                if means.len() < self.sample_means.len() {
-                    self.strings[3] = "Mean is invalid".to_owned();
-                    return;
-                };
-
-                let devs: Vec<_> = self
-                    .sample_devs
-                    .iter()
-                    .filter_map(|x| x.eval().ok())
-                    .collect();
-                if devs.len() < self.sample_devs.len() {
-                    self.strings[3] = "Standard deviation is invalid".to_owned();
-                    return;
-                };
-
-                let pooled = means
-                    .iter()
-                    .zip(self.sample_sizes.iter())
-                    .map(|(m, s)| m * *s as f64)
-                    .sum::<f64>()
-                    / (self.sample_sizes.iter().sum::<usize>() as f64);
-
-                self.strings[0] = pooled.to_string();
-
-                let free = match self.hypothesis {
-                    Constr::NE(_) => self.sample_means.len(),
-                    Constr::NENone => self.sample_means.len() - 1,
-                    _ => {
-                        self.strings[3] = "Not a valid hypothesis".to_string();
-                        return;
-                    }
-                } as f64;
-                let Ok(n) = ChiSquared::new(free) else {
-                self.strings[3] = "Not a valid Chi squared distr".to_string();
-                return;
-            };
-
-                if !matches!(self.hypothesis, Constr::NE(_) | Constr::NENone) {
-                    self.strings[2] = "Not a valid hypothesis".to_string();
-                    return;
+            let means: Vec<_> = self
+                .sample_means
+                .iter()
+                .filter_map(|x| x.eval().ok())
+                .collect();
+            self.strings[0] = self.pooled(&means).to_string();
+            let h0 = self.hypothesis.point().map_or(0.0, |v| *v as f64);
+            // Large statistics are evidence against H0 whatever the hypothesis
+            match self.test(Alternative::Greater, h0, self.alpha as f64, 0.0) {
+                Ok(r) => {
+                    self.pval = r.pval as f32;
+                    self.region = r.region;
+                    self.strings[2] = self.pval.to_string();
+                    self.strings[5] = r.stat.to_string();
+                    self.strings[6] = r.null.df();
+                    self.strings[7] = self.region.to_string();
+                    self.strings[8] = r.conclusion(self.alpha as f64);
+                    self.strings[3].clear();
                 }
-
-                let crit = means
-                    .iter()
-                    .zip(devs.iter())
-                    .zip(self.sample_sizes.iter())
-                    .map(|((m, d), s)| match self.hypothesis {
-                        Constr::NE(v) => (*m - v as f64).powi(2) / (d * d / (*s as f64)),
-                        Constr::NENone => (m - pooled).powi(2) / (d * d / (*s as f64)),
-                        _ => {
-                            unreachable!("Not a valid hypothesis");
-                        }
-                    })
-                    .sum();
-
-                self.pval = 1.0 - n.cdf(crit) as f32;
-
-                self.strings[2] = self.pval.to_string();
-                self.strings[3].clear();
-            };
-            f();
+                Err(e) => self.strings[3] = e,
+            }
         }
         ui.num_box("", &mut self.strings[2].clone());
+        ui.test_results(
+            &self.strings[5],
+            &self.strings[6],
+            &self.region,
+            &self.strings[8],
+        );
         ui.label(&self.strings[3]);
         resp
     }
 }
 
 impl KStats {
+    /// Mean of all the samples together.
+    fn pooled(&self, means: &[f64]) -> f64 {
+        means
+            .iter()
+            .zip(self.sample_sizes.iter())
+            .map(|(m, s)| m * *s as f64)
+            .sum::<f64>()
+            / (self.sample_sizes.iter().sum::<usize>() as f64)
+    }
+
     fn vfill(&mut self) {
         if let Ok(val) = self.strings[1].parse() {
             self.hypothesis = val;
         }
+        if let Ok(val) = self.strings[4].parse() {
+            self.alpha = val;
+        }
         for (((m, d), s), st) in self
             .sample_means
             .iter_mut()
@@ -1485,8 +2212,25 @@ impl SampleStat {
 pub(crate) struct RCTable {
     sample_vals: Vec<Vec<Option<f32>>>,
     kstrings: Vec<Vec<String>>,
+    row_names: Vec<String>,
+    col_names: Vec<String>,
+    yates: bool,
+    mode: TwoByTwoMode,
     pval: f32,
-    strings: [String; 2],
+    exact: Vec<Vec<String>>,
+    measures: Vec<Vec<String>>,
+    observed: Vec<Vec<String>>,
+    expected: Vec<Vec<String>>,
+    residuals: Vec<Vec<String>>,
+    /// p value, error, statistic, df, effect size, warning, confidence, prevalence, exact test error
+    strings: [String; 9],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum TwoByTwoMode {
+    #[default]
+    Risk,
+    Diagnostic,
 }
 
 impl Default for RCTable {
@@ -1494,8 +2238,27 @@ impl Default for RCTable {
         Self {
             sample_vals: vec![],
             kstrings: vec![],
+            row_names: vec![],
+            col_names: vec![],
+            yates: false,
+            mode: TwoByTwoMode::Risk,
             pval: 0.,
-            strings: ["".to_string(), "".to_string()],
+            exact: vec![],
+            measures: vec![],
+            observed: vec![],
+            expected: vec![],
+            residuals: vec![],
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "0.95".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
         }
     }
 }
@@ -1531,7 +2294,7 @@ fn get_all_fill<T>(g: &[Vec<Option<T>>], fill: (usize, usize)) -> bool {
 }
 
 fn get_shape<T>(g: &[Vec<T>]) -> (usize, usize) {
-    (g.get(0).map(|x| x.len()).unwrap_or(0), g.len())
+    (g.first().map(|x| x.len()).unwrap_or(0), g.len())
 }
 
 fn add_row<T: Default>(g: &mut Vec<Vec<T>>) {
@@ -1562,10 +2325,21 @@ impl Widget for &mut RCTable {
     fn ui(self, ui: &mut Ui) -> egui::Response {
         let mut resp = ui.label("Enter values, checks independence of rows");
         let respm = resp.clone();
-        for mv in self.kstrings.iter_mut() {
+        resp = resp.union(
+            ui.horizontal(|ui| {
+                let mut resp = respm.clone();
+                ui.add_space(68.);
+                for n in self.col_names.iter_mut() {
+                    resp = resp.union(ui.grid_num_box(40, n));
+                }
+                resp
+            })
+            .inner,
+        );
+        for (mv, n) in self.kstrings.iter_mut().zip(self.row_names.iter_mut()) {
             resp = resp.union(
                 ui.horizontal(|ui| {
-                    let mut resp = respm.clone();
+                    let mut resp = ui.grid_num_box(60, n);
                     for m in mv.iter_mut() {
                         resp = resp.union(ui.grid_num_box(40, m));
                     }
@@ -1579,7 +2353,6 @@ impl Widget for &mut RCTable {
         }
         let s = get_shape(&self.sample_vals);
         let fs = get_fill_shape(&self.sample_vals);
-        // println!("{:?}, {:?}", s, fs);
         if s.0 == fs.0 {
             add_col(&mut self.kstrings);
             add_col(&mut self.sample_vals);
@@ -1596,115 +2369,2388 @@ impl Widget for &mut RCTable {
             rem_row(&mut self.kstrings);
             rem_row(&mut self.sample_vals);
         }
-        if resp.changed() {
-            let mut f = || {
-                // println!("{:?} {:?}", fs, get_all_fill(&self.sample_vals));
+        let s = get_shape(&self.sample_vals);
+        self.col_names.resize(s.0, String::new());
+        self.row_names.resize(s.1, String::new());
 
-                if !get_all_fill(&self.sample_vals, fs) {
-                    self.strings[1] = "Not a filled table".to_string();
-                    return;
+        if fs == (2, 2) {
+            if ui.checkbox(&mut self.yates, "Yates' correction").changed() {
+                resp.mark_changed();
+            }
+            resp = resp.union(ui.num_box("confidence", &mut self.strings[6]));
+            ui.horizontal(|ui| {
+                for (m, name) in [
+                    (
+                        TwoByTwoMode::Risk,
+                        "Rows exposed/unexposed, columns event/none",
+                    ),
+                    (
+                        TwoByTwoMode::Diagnostic,
+                        "Rows test +/-, columns disease +/-",
+                    ),
+                ] {
+                    if ui.radio_value(&mut self.mode, m, name).changed() {
+                        resp.mark_changed();
+                    }
                 }
+            });
+            if self.mode == TwoByTwoMode::Diagnostic {
+                resp =
+                    resp.union(ui.num_box("prevalence (blank for sample)", &mut self.strings[7]));
+            }
+        }
+        if resp.changed() {
+            self.observed.clear();
+            self.exact.clear();
+            self.measures.clear();
+            for s in self.strings[..6].iter_mut() {
+                s.clear();
+            }
+            self.strings[8].clear();
+            if let Err(e) = self.calc(fs) {
+                self.strings[1] = e.to_string();
+            }
+        }
+        if !self.observed.is_empty() {
+            let cols: Vec<String> = (0..fs.0).map(|j| self.col_name(j)).collect();
+            let mut header: Vec<&str> = vec![""];
+            header.extend(cols.iter().map(|c| c.as_str()));
+            header.push("Total");
+            ui.label("Observed");
+            ui.table("rc observed", &header, &self.observed);
+            header.pop();
+            ui.label("Expected");
+            ui.table("rc expected", &header, &self.expected);
+            ui.label("Standardized residuals");
+            ui.table("rc residuals", &header, &self.residuals);
+        }
+        ui.num_box("Chi square", &mut self.strings[2].clone());
+        ui.num_box("df", &mut self.strings[3].clone());
+        ui.num_box("p", &mut self.strings[0].clone());
+        ui.num_box(
+            if fs == (2, 2) { "Phi" } else { "Cramér's V" },
+            &mut self.strings[4].clone(),
+        );
+        ui.label(RichText::new(&self.strings[5]).color(Color32::GOLD));
+        if !self.measures.is_empty() {
+            ui.table(
+                "rc measures",
+                &["Measure", "Estimate", "CI"],
+                &self.measures,
+            );
+        }
+        if !self.exact.is_empty() {
+            ui.label("Exact tests, McNemar treats rows as before and columns as after");
+            ui.table(
+                "rc exact",
+                &["Test", "p", "p less", "p greater", "Odds ratio", "CI"],
+                &self.exact,
+            );
+        }
+        if !self.strings[8].is_empty() {
+            ui.label("Exact tests");
+            ui.label(RichText::new(&self.strings[8]).color(Color32::DARK_RED));
+        }
+        ui.label(&self.strings[1]);
+        resp
+    }
+}
 
-                let pooled = self
-                    .sample_vals
-                    .iter()
-                    .cloned()
-                    .reduce(|x, y| {
-                        x.into_iter()
-                            .zip(y.into_iter())
-                            .map(|(x, y)| {
-                                let x = x.unwrap_or(0.);
-                                let y = y.unwrap_or(0.);
-                                Some(x + y)
-                            })
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap();
+impl RCTable {
+    fn vfill(&mut self) {
+        for (sr, vr) in self.kstrings.iter().zip(self.sample_vals.iter_mut()) {
+            for (s, v) in sr.iter().zip(vr.iter_mut()) {
+                *v = s.parse::<f32>().ok();
+            }
+        }
+    }
 
-                let pooled_row = self
-                    .sample_vals
-                    .iter()
-                    .filter_map(|x| {
-                        x.iter().fold(Some(0.), |x, y| {
-                            let x = x.unwrap_or(0.);
-                            let y = y.unwrap_or(0.);
-                            Some(x + y)
-                        })
-                    })
-                    .collect::<Vec<_>>();
+    fn row_name(&self, i: usize) -> String {
+        match self.row_names.get(i) {
+            Some(n) if !n.is_empty() => n.clone(),
+            _ => format!("Row {}", i + 1),
+        }
+    }
 
-                if pooled_row.is_empty() {
-                    self.strings[1] = "Not large enough table".to_string();
-                    return;
-                }
+    fn col_name(&self, j: usize) -> String {
+        match self.col_names.get(j) {
+            Some(n) if !n.is_empty() => n.clone(),
+            _ => format!("Col {}", j + 1),
+        }
+    }
+
+    /// The filled part of the table.
+    fn counts(&self, fs: (usize, usize)) -> Result<Vec<Vec<f64>>, &'static str> {
+        if !get_all_fill(&self.sample_vals, fs) {
+            return Err("Not a filled table");
+        }
+        if fs.0 < 2 || fs.1 < 2 {
+            return Err("Not large enough table");
+        }
+        Ok(self.sample_vals[..fs.1]
+            .iter()
+            .map(|r| r[..fs.0].iter().map(|v| v.unwrap_or(0.) as f64).collect())
+            .collect())
+    }
+
+    fn calc(&mut self, fs: (usize, usize)) -> Result<(), &'static str> {
+        let obs = self.counts(fs)?;
+        let rows: Vec<f64> = obs.iter().map(|r| r.iter().sum()).collect();
+        let cols: Vec<f64> = (0..fs.0).map(|j| obs.iter().map(|r| r[j]).sum()).collect();
+        let total: f64 = rows.iter().sum();
+        if rows.iter().chain(cols.iter()).any(|t| *t <= 0.0) {
+            return Err("Every row and column needs a nonzero total");
+        }
+        let exp: Vec<Vec<f64>> = rows
+            .iter()
+            .map(|r| cols.iter().map(|c| r * c / total).collect())
+            .collect();
+
+        let yates = fs == (2, 2) && self.yates;
+        let mut crit = 0.0;
+        let mut plain = 0.0;
+        for (er, or) in exp.iter().zip(obs.iter()) {
+            for (e, o) in er.iter().zip(or.iter()) {
+                let d = (o - e).abs();
+                let dc = if yates { d - d.min(0.5) } else { d };
+                crit += dc * dc / e;
+                plain += d * d / e;
+            }
+        }
+        let free = ((fs.0 - 1) * (fs.1 - 1)) as f64;
+        let Ok(n) = ChiSquared::new(free) else {
+            return Err("Not a valid Chi squared distr");
+        };
+        self.pval = 1.0 - n.cdf(crit) as f32;
+
+        self.observed = obs
+            .iter()
+            .zip(rows.iter())
+            .enumerate()
+            .map(|(i, (r, t))| {
+                std::iter::once(self.row_name(i))
+                    .chain(r.iter().map(|v| v.to_string()))
+                    .chain(std::iter::once(t.to_string()))
+                    .collect()
+            })
+            .chain(std::iter::once(
+                std::iter::once("Total".to_string())
+                    .chain(cols.iter().map(|v| v.to_string()))
+                    .chain(std::iter::once(total.to_string()))
+                    .collect(),
+            ))
+            .collect();
+        self.expected = exp
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                std::iter::once(self.row_name(i))
+                    .chain(r.iter().map(|v| fmt_num(*v)))
+                    .collect()
+            })
+            .collect();
+        // Adjusted residuals, roughly standard normal under independence
+        self.residuals = obs
+            .iter()
+            .zip(exp.iter())
+            .enumerate()
+            .map(|(i, (or, er))| {
+                std::iter::once(self.row_name(i))
+                    .chain(or.iter().zip(er.iter()).enumerate().map(|(j, (o, e))| {
+                        fmt_num(
+                            (o - e)
+                                / (e * (1.0 - rows[i] / total) * (1.0 - cols[j] / total)).sqrt(),
+                        )
+                    }))
+                    .collect()
+            })
+            .collect();
+
+        self.strings[0] = self.pval.to_string();
+        self.strings[2] = crit.to_string();
+        self.strings[3] = free.to_string();
+        self.strings[4] = if fs == (2, 2) {
+            let phi = (obs[0][0] * obs[1][1] - obs[0][1] * obs[1][0])
+                / (rows[0] * rows[1] * cols[0] * cols[1]).sqrt();
+            phi.to_string()
+        } else {
+            let m = (fs.0.min(fs.1) - 1) as f64;
+            (plain / (total * m)).sqrt().to_string()
+        };
+        let small = exp.iter().flatten().filter(|e| **e < 5.0).count();
+        if small > 0 {
+            self.strings[5] = format!(
+                "{small} of {} expected counts are below 5, the chi square approximation may be poor",
+                fs.0 * fs.1
+            );
+            if fs == (2, 2) {
+                self.strings[5].push_str(", use Fisher's exact test");
+            }
+        }
+        if fs == (2, 2) {
+            let t = [[obs[0][0], obs[0][1]], [obs[1][0], obs[1][1]]];
+            self.calc_measures(t)?;
+            // Real valued tables still get the chi square, only the exact tests need counts
+            if let Err(e) = self.calc_exact(t) {
+                self.strings[8] = e.to_string();
+            }
+        }
+        Ok(())
+    }
 
-                let total = pooled_row.iter().cloned().reduce(|x, y| x + y).unwrap();
+    fn calc_measures(&mut self, t: [[f64; 2]; 2]) -> Result<(), &'static str> {
+        let confidence = self.strings[6]
+            .parse::<Expr>()
+            .ok()
+            .and_then(|e| e.eval().ok())
+            .filter(|c| *c > 0.0 && *c < 1.0)
+            .ok_or("Confidence must be between 0 and 1")?;
+        let z = Normal::new(0.0, 1.0)
+            .expect("SND cant fail")
+            .inverse_cdf((1.0 + confidence) / 2.0);
+        let [[a, b], [c, d]] = t;
+        let row = |name: &str, est: f64, lo: f64, hi: f64| {
+            vec![
+                name.to_string(),
+                fmt_num(est),
+                Constr::In(fmt_num(lo), fmt_num(hi)).to_string(),
+            ]
+        };
+        // Interval for a ratio from the standard error of its log
+        let log_row = |name: &str, est: f64, se: f64| {
+            row(name, est, est * (-z * se).exp(), est * (z * se).exp())
+        };
+        let prop_row = |name: &str, x: f64, n: f64| {
+            let (lo, hi) = clopper_pearson(x, n, 1.0 - confidence);
+            row(name, x / n, lo, hi)
+        };
+        // Ratios blow up on a zero cell, so they take the Haldane-Anscombe 0.5 correction
+        let zero = t.iter().flatten().any(|v| *v == 0.0);
+        let [[ha, hb], [hc, hd]] = if zero {
+            t.map(|r| r.map(|v| v + 0.5))
+        } else {
+            t
+        };
+        let ratio = |name: &str| {
+            if zero {
+                format!("{name} (0.5 added to each cell)")
+            } else {
+                name.to_string()
+            }
+        };
+
+        self.measures = match self.mode {
+            TwoByTwoMode::Risk => {
+                let (n1, n2) = (a + b, c + d);
+                let (r1, r2) = (a / n1, c / n2);
+                let rd_se = (r1 * (1.0 - r1) / n1 + r2 * (1.0 - r2) / n2).sqrt();
+                vec![
+                    prop_row("Risk exposed", a, n1),
+                    prop_row("Risk unexposed", c, n2),
+                    row(
+                        "Risk difference",
+                        r1 - r2,
+                        r1 - r2 - z * rd_se,
+                        r1 - r2 + z * rd_se,
+                    ),
+                    log_row(
+                        &ratio("Relative risk"),
+                        (ha / (ha + hb)) / (hc / (hc + hd)),
+                        (1.0 / ha - 1.0 / (ha + hb) + 1.0 / hc - 1.0 / (hc + hd)).sqrt(),
+                    ),
+                    log_row(
+                        &ratio("Odds ratio"),
+                        ha * hd / (hb * hc),
+                        (1.0 / ha + 1.0 / hb + 1.0 / hc + 1.0 / hd).sqrt(),
+                    ),
+                ]
+            }
+            TwoByTwoMode::Diagnostic => {
+                let sens = a / (a + c);
+                let spec = d / (b + d);
+                let prev = if self.strings[7].trim().is_empty() {
+                    (a + c) / (a + b + c + d)
+                } else {
+                    self.strings[7]
+                        .parse::<Expr>()
+                        .ok()
+                        .and_then(|e| e.eval().ok())
+                        .filter(|p| *p >= 0.0 && *p <= 1.0)
+                        .ok_or("Prevalence must be between 0 and 1")?
+                };
+                // Bayes' theorem with the chosen prevalence
+                let ppv = sens * prev / (sens * prev + (1.0 - spec) * (1.0 - prev));
+                let npv = spec * (1.0 - prev) / (spec * (1.0 - prev) + (1.0 - sens) * prev);
+                vec![
+                    prop_row("Sensitivity", a, a + c),
+                    prop_row("Specificity", d, b + d),
+                    vec!["Prevalence".to_string(), fmt_num(prev), "".to_string()],
+                    vec!["PPV".to_string(), fmt_num(ppv), "".to_string()],
+                    vec!["NPV".to_string(), fmt_num(npv), "".to_string()],
+                    log_row(
+                        &ratio("LR+"),
+                        (ha / (ha + hc)) / (hb / (hb + hd)),
+                        (1.0 / ha - 1.0 / (ha + hc) + 1.0 / hb - 1.0 / (hb + hd)).sqrt(),
+                    ),
+                    log_row(
+                        &ratio("LR-"),
+                        (hc / (ha + hc)) / (hd / (hb + hd)),
+                        (1.0 / hc - 1.0 / (ha + hc) + 1.0 / hd - 1.0 / (hb + hd)).sqrt(),
+                    ),
+                ]
+            }
+        };
+        Ok(())
+    }
 
-                let pooled_p = pooled
-                    .into_iter()
-                    .map(|x| x.unwrap() / total)
-                    .collect::<Vec<_>>();
-                let pooled_row_p = pooled_row
-                    .into_iter()
-                    .map(|x| x / total)
-                    .collect::<Vec<_>>();
+    fn calc_exact(&mut self, t: [[f64; 2]; 2]) -> Result<(), &'static str> {
+        if t.iter().flatten().any(|v| v.fract() != 0.0 || *v < 0.0) {
+            return Err("Exact tests need whole number counts");
+        }
+        let confidence = self.strings[6]
+            .parse::<Expr>()
+            .ok()
+            .and_then(|e| e.eval().ok())
+            .filter(|c| *c > 0.0 && *c < 1.0)
+            .ok_or("Confidence must be between 0 and 1")?;
+        let alpha = 1.0 - confidence;
+        let [[a, b], [c, d]] = t.map(|r| r.map(|v| v as u64));
+
+        let f = fisher_exact(a, b, c, d, alpha);
+        let m = mcnemar_exact(b, c, alpha);
+        let row = |name: &str, (p, pl, pg, or, lo, hi): ExactResult| {
+            vec![
+                name.to_string(),
+                fmt_num(p),
+                fmt_num(pl),
+                fmt_num(pg),
+                fmt_num(or),
+                Constr::In(fmt_num(lo), fmt_num(hi)).to_string(),
+            ]
+        };
+        self.exact = vec![row("Fisher", f), row("McNemar", m)];
+        Ok(())
+    }
+}
 
-                let exp = (0..fs.1)
-                    .map(|i| {
-                        (0..fs.0)
-                            .map(|j| total * pooled_p[j] * pooled_row_p[i])
-                            .collect::<Vec<_>>()
-                    })
-                    .collect::<Vec<_>>();
+/// Two sided, less and greater p values, then the odds ratio and its interval.
+type ExactResult = (f64, f64, f64, f64, f64, f64);
+
+/// Fisher's exact test on the table [[a, b], [c, d]], conditioning on the margins.
+///
+/// The odds ratio is the conditional maximum likelihood estimate, with an interval
+/// from inverting the noncentral hypergeometric tails.
+fn fisher_exact(a: u64, b: u64, c: u64, d: u64, alpha: f64) -> ExactResult {
+    let (r1, c1, n) = (a + b, a + c, a + b + c + d);
+    let lo = (r1 + c1).saturating_sub(n);
+    let hi = r1.min(c1);
+    let ln_w: Vec<f64> = (lo..=hi)
+        .map(|x| {
+            function::factorial::ln_binomial(c1, x)
+                + function::factorial::ln_binomial(n - c1, r1 - x)
+        })
+        .collect();
+    // Probabilities of each x under odds ratio psi
+    let probs = |ln_psi: f64| -> Vec<f64> {
+        let w: Vec<f64> = ln_w
+            .iter()
+            .enumerate()
+            .map(|(i, l)| l + (lo + i as u64) as f64 * ln_psi)
+            .collect();
+        let m = w.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let e: Vec<f64> = w.iter().map(|w| (w - m).exp()).collect();
+        let t: f64 = e.iter().sum();
+        e.into_iter().map(|e| e / t).collect()
+    };
+    let k = (a - lo) as usize;
+    let p0 = probs(0.0);
+    let pl = p0[..=k].iter().sum::<f64>().min(1.0);
+    let pg = p0[k..].iter().sum::<f64>().min(1.0);
+    let p = p0
+        .iter()
+        .filter(|p| **p <= p0[k] * (1.0 + 1e-7))
+        .sum::<f64>()
+        .min(1.0);
+
+    // All of these are increasing in psi, so bisect on log psi
+    let solve = |f: &dyn Fn(f64) -> f64, target: f64| {
+        let (mut l, mut h) = (-50.0, 50.0);
+        for _ in 0..200 {
+            let m = (l + h) / 2.0;
+            if f(m) < target {
+                l = m;
+            } else {
+                h = m;
+            }
+        }
+        ((l + h) / 2.0).exp()
+    };
+    let mean = |ln_psi: f64| {
+        probs(ln_psi)
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (lo + i as u64) as f64 * p)
+            .sum::<f64>()
+    };
+    let or = if a == lo {
+        0.0
+    } else if a == hi {
+        f64::INFINITY
+    } else {
+        solve(&mean, a as f64)
+    };
+    let ci_lo = if a == lo {
+        0.0
+    } else {
+        solve(&|l| probs(l)[k..].iter().sum(), alpha / 2.0)
+    };
+    let ci_hi = if a == hi {
+        f64::INFINITY
+    } else {
+        solve(&|l| -probs(l)[..=k].iter().sum::<f64>(), -alpha / 2.0)
+    };
+    (p, pl, pg, or, ci_lo, ci_hi)
+}
 
-                if fs.0 < 1 || fs.1 < 1 {
-                    self.strings[1] = "Not large enough table".to_string();
-                    return;
-                }
-                let free = ((fs.0 - 1) * (fs.1 - 1)) as f64;
+/// Exact interval for a binomial proportion of x out of n.
+fn clopper_pearson(x: f64, n: f64, alpha: f64) -> (f64, f64) {
+    let lo = if x <= 0.0 {
+        0.0
+    } else {
+        Beta::new(x, n - x + 1.0)
+            .map(|d| d.inverse_cdf(alpha / 2.0))
+            .unwrap_or(0.0)
+    };
+    let hi = if x >= n {
+        1.0
+    } else {
+        Beta::new(x + 1.0, n - x)
+            .map(|d| d.inverse_cdf(1.0 - alpha / 2.0))
+            .unwrap_or(1.0)
+    };
+    (lo, hi)
+}
+
+/// McNemar's exact test on the discordant pairs of a paired table.
+///
+/// The odds ratio is b / c, with an interval from the Clopper-Pearson interval of b / (b + c).
+fn mcnemar_exact(b: u64, c: u64, alpha: f64) -> ExactResult {
+    let m = b + c;
+    if m == 0 {
+        return (1.0, 1.0, 1.0, f64::NAN, 0.0, f64::INFINITY);
+    }
+    let bin = Binomial::new(0.5, m).expect("p of 0.5 is valid");
+    let pl = bin.cdf(b);
+    let pg = 1.0 - bin.cdf(b) + bin.pmf(b);
+    let p = (2.0 * pl.min(pg)).min(1.0);
+    let (bf, mf) = (b as f64, m as f64);
+    let (plo, phi) = clopper_pearson(bf, mf, alpha);
+    (
+        p,
+        pl,
+        pg,
+        bf / c as f64,
+        plo / (1.0 - plo),
+        phi / (1.0 - phi),
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum SsType {
+    One,
+    Two,
+    #[default]
+    Three,
+}
+
+#[derive(Clone)]
+pub(crate) struct TwoWayAnova {
+    ss_type: SsType,
+    table: Vec<Vec<String>>,
+    a_levels: Vec<String>,
+    b_levels: Vec<String>,
+    /// Indexed by level of B then level of A
+    cell_means: Vec<Vec<Option<f64>>>,
+    /// response, factor a, factor b, error
+    strings: [String; 4],
+}
+
+impl Default for TwoWayAnova {
+    fn default() -> Self {
+        Self {
+            ss_type: SsType::Three,
+            table: vec![],
+            a_levels: vec![],
+            b_levels: vec![],
+            cell_means: vec![],
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+/// Sum to zero coded columns for a factor, one less than the number of levels.
+fn effect_cols(idx: &[usize], count: usize) -> Vec<Vec<f64>> {
+    (0..count - 1)
+        .map(|j| {
+            idx.iter()
+                .map(|&i| {
+                    if i == j {
+                        1.0
+                    } else if i == count - 1 {
+                        -1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn anova_row(name: &str, df: f64, ss: f64, mse: f64, dfe: f64) -> Vec<String> {
+    let ms = ss / df;
+    let f = ms / mse;
+    let p = match FisherSnedecor::new(df, dfe) {
+        Ok(d) if f.is_finite() => fmt_num(1.0 - d.cdf(f)),
+        _ => "".to_string(),
+    };
+    vec![
+        name.to_string(),
+        df.to_string(),
+        fmt_num(ss),
+        fmt_num(ms),
+        fmt_num(f),
+        p,
+    ]
+}
+
+impl TwoWayAnova {
+    fn calc(&mut self) -> Result<(), String> {
+        let y = parse_data(&self.strings[0])?;
+        let a = parse_labels(&self.strings[1]);
+        let b = parse_labels(&self.strings[2]);
+        if y.len() != a.len() || y.len() != b.len() {
+            return Err("Response and factors must be the same length".to_string());
+        }
+        let (a_levels, a_idx) = levels(&a);
+        let (b_levels, b_idx) = levels(&b);
+        if a_levels.len() < 2 || b_levels.len() < 2 {
+            return Err("Each factor needs at least 2 levels".to_string());
+        }
+        let (la, lb) = (a_levels.len(), b_levels.len());
+
+        let mut sums = vec![vec![(0.0, 0usize); la]; lb];
+        for ((y, i), j) in y.iter().zip(a_idx.iter()).zip(b_idx.iter()) {
+            sums[*j][*i].0 += y;
+            sums[*j][*i].1 += 1;
+        }
+        if sums.iter().flatten().any(|(_, c)| *c == 0) {
+            return Err("Every combination of factor levels needs an observation".to_string());
+        }
+        self.cell_means = sums
+            .iter()
+            .map(|r| r.iter().map(|(s, c)| Some(s / *c as f64)).collect())
+            .collect();
+        self.a_levels = a_levels;
+        self.b_levels = b_levels;
+
+        let n = y.len();
+        let dfe = (n - la * lb) as f64;
+        if dfe < 1.0 {
+            return Err("Some cell needs more than one observation".to_string());
+        }
+        if y.iter().all(|v| *v == y[0]) {
+            return Err("Every response is the same, so there is nothing to compare".to_string());
+        }
 
-                let Ok(n) = ChiSquared::new(free) else {
-                self.strings[1] = "Not a valid Chi squared distr".to_string();
-                return;
+        let ones = vec![vec![1.0; n]];
+        let ca = effect_cols(&a_idx, la);
+        let cb = effect_cols(&b_idx, lb);
+        let cab: Vec<Vec<f64>> = ca
+            .iter()
+            .flat_map(|x| {
+                cb.iter()
+                    .map(|z| x.iter().zip(z.iter()).map(|(x, z)| x * z).collect())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let rss = |parts: &[&Vec<Vec<f64>>]| -> Result<f64, String> {
+            let cols: Vec<Vec<f64>> = parts.iter().flat_map(|p| p.iter().cloned()).collect();
+            Ok(lstsq(&cols, &y)?.rss)
+        };
+
+        let full = rss(&[&ones, &ca, &cb, &cab])?;
+        let main = rss(&[&ones, &ca, &cb])?;
+        let (ss_a, ss_b) = match self.ss_type {
+            SsType::One => (
+                rss(&[&ones])? - rss(&[&ones, &ca])?,
+                rss(&[&ones, &ca])? - main,
+            ),
+            SsType::Two => (rss(&[&ones, &cb])? - main, rss(&[&ones, &ca])? - main),
+            SsType::Three => (
+                rss(&[&ones, &cb, &cab])? - full,
+                rss(&[&ones, &ca, &cab])? - full,
+            ),
+        };
+        // The interaction is adjusted for both main effects under every type
+        let ss_ab = main - full;
+        let sst = rss(&[&ones])?;
+        if full <= 1e-12 * sst {
+            return Err(
+                "No cell has any spread, so the error mean square and F are undefined".to_string(),
+            );
+        }
+        let mse = full / dfe;
+
+        self.table = vec![
+            anova_row("Factor A", (la - 1) as f64, ss_a, mse, dfe),
+            anova_row("Factor B", (lb - 1) as f64, ss_b, mse, dfe),
+            anova_row("A x B", ((la - 1) * (lb - 1)) as f64, ss_ab, mse, dfe),
+            vec![
+                "Error".to_string(),
+                dfe.to_string(),
+                fmt_num(full),
+                fmt_num(mse),
+                "".to_string(),
+                "".to_string(),
+            ],
+            vec![
+                "Total".to_string(),
+                (n - 1).to_string(),
+                fmt_num(sst),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        ];
+        Ok(())
+    }
+}
+
+impl Widget for &mut TwoWayAnova {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        ui.label("One observation per line, factors can be any labels");
+        let mut resp = ui
+            .horizontal(|ui| {
+                let resp = ui.data_box("Response", &mut self.strings[0]);
+                let resp = resp.union(ui.data_box("Factor A", &mut self.strings[1]));
+                resp.union(ui.data_box("Factor B", &mut self.strings[2]))
+            })
+            .inner;
+        ui.horizontal(|ui| {
+            ui.label("Sums of squares");
+            for (t, name) in [
+                (SsType::One, "Type I"),
+                (SsType::Two, "Type II"),
+                (SsType::Three, "Type III"),
+            ] {
+                if ui.radio_value(&mut self.ss_type, t, name).changed() {
+                    resp.mark_changed();
+                }
+            }
+        });
+        if resp.changed() {
+            self.table.clear();
+            self.cell_means.clear();
+            self.strings[3] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
             };
+        }
+        if !self.table.is_empty() {
+            ui.table(
+                "anova",
+                &["Source", "df", "SS", "MS", "F", "p"],
+                &self.table,
+            );
+        }
+        if !self.cell_means.is_empty() {
+            let names = self.a_levels.clone();
+            Plot::new("Interaction")
+                .height(250.)
+                .legend(Legend::default())
+                .x_axis_formatter(move |x, _| {
+                    if x.fract() == 0.0 && x >= 0.0 {
+                        names.get(x as usize).cloned().unwrap_or_default()
+                    } else {
+                        "".to_string()
+                    }
+                })
+                .show(ui, |ui| {
+                    for (name, r) in self.b_levels.iter().zip(self.cell_means.iter()) {
+                        let pts: Vec<[f64; 2]> = r
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, m)| m.map(|m| [i as f64, m]))
+                            .collect();
+                        ui.line(Line::new(pts.clone()).name(name));
+                        ui.points(Points::new(pts).radius(3.).name(name));
+                    }
+                });
+        }
+        ui.label(RichText::new(&self.strings[3]).color(Color32::DARK_RED));
+        resp
+    }
+}
 
-                // println!("{:?}\n{:?}\n", exp, self.sample_vals);
+/// Two sided critical value of a t distribution.
+fn t_crit(df: f64, confidence: f64) -> f64 {
+    StudentsT::new(0.0, 1.0, df)
+        .map(|t| t.inverse_cdf((1.0 + confidence) / 2.0))
+        .unwrap_or(f64::NAN)
+}
 
-                let crit = exp
-                    .iter()
-                    .zip(self.sample_vals.iter())
-                    .map(|(e, o)| {
-                        e.iter()
-                            .zip(o.iter())
-                            .map(|(e, o)| (e - (*o).unwrap()).powi(2) / e)
-                            .sum::<f32>()
-                    })
-                    .sum::<f32>() as f64;
+/// Two sided p value of a t statistic.
+fn t_pval(t: f64, df: f64) -> f64 {
+    // statrs panics on a NaN, which a perfect fit gives as 0 / 0
+    if t.is_nan() {
+        return f64::NAN;
+    }
+    StudentsT::new(0.0, 1.0, df)
+        .map(|d| 2.0 * (1.0 - d.cdf(t.abs())))
+        .unwrap_or(f64::NAN)
+}
+
+#[derive(Clone)]
+pub(crate) struct LinReg {
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    fitted: Vec<f64>,
+    resid: Vec<f64>,
+    summary: Vec<Vec<String>>,
+    table: Vec<Vec<String>>,
+    /// x data, y data, x for intervals, confidence, mean response, prediction, error
+    strings: [String; 7],
+}
 
-                // println!("{crit}");
+impl Default for LinReg {
+    fn default() -> Self {
+        Self {
+            xs: vec![],
+            ys: vec![],
+            fitted: vec![],
+            resid: vec![],
+            summary: vec![],
+            table: vec![],
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "0.0".to_string(),
+                "0.95".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
 
-                self.pval = 1.0 - n.cdf(crit) as f32;
+impl LinReg {
+    fn calc(&mut self) -> Result<(), String> {
+        let xs = parse_data(&self.strings[0])?;
+        let ys = parse_data(&self.strings[1])?;
+        if xs.len() != ys.len() {
+            return Err("x and y must be the same length".to_string());
+        }
+        let n = xs.len();
+        if n < 3 {
+            return Err("Need at least 3 points".to_string());
+        }
+        let fit = lstsq(&[vec![1.0; n], xs.clone()], &ys)?;
+        let df = (n - 2) as f64;
+        let se = (fit.rss / df).sqrt();
+        let ybar = ys.iter().sum::<f64>() / n as f64;
+        let sst = ys.iter().map(|y| (y - ybar).powi(2)).sum::<f64>();
+        if sst == 0.0 {
+            return Err(
+                "All y values are the same, r² is undefined and there is nothing to fit"
+                    .to_string(),
+            );
+        }
+        let r2 = 1.0 - fit.rss / sst;
+        let r = r2.max(0.0).sqrt() * fit.coefs[1].signum();
+
+        self.summary = vec![
+            vec!["Slope".to_string(), fmt_num(fit.coefs[1])],
+            vec!["Intercept".to_string(), fmt_num(fit.coefs[0])],
+            vec!["r".to_string(), fmt_num(r)],
+            vec!["r²".to_string(), fmt_num(r2)],
+            vec!["SE of estimate".to_string(), fmt_num(se)],
+        ];
+
+        let confidence = self.strings[3]
+            .parse::<Expr>()
+            .ok()
+            .and_then(|e| e.eval().ok())
+            .filter(|c| *c > 0.0 && *c < 1.0)
+            .ok_or("Confidence must be between 0 and 1")?;
+        let crit = t_crit(df, confidence);
+        self.table = ["Intercept", "Slope"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let b = fit.coefs[i];
+                let sb = se * fit.xtx_inv[i][i].sqrt();
+                let t = b / sb;
+                vec![
+                    name.to_string(),
+                    fmt_num(b),
+                    fmt_num(sb),
+                    fmt_num(t),
+                    fmt_num(t_pval(t, df)),
+                    Constr::In(fmt_num(b - crit * sb), fmt_num(b + crit * sb)).to_string(),
+                ]
+            })
+            .collect();
+
+        let x0 = self.strings[2]
+            .parse::<Expr>()
+            .ok()
+            .and_then(|e| e.eval().ok())
+            .ok_or("x for the intervals is invalid")?;
+        let h = fit.xtx_inv[0][0] + 2.0 * x0 * fit.xtx_inv[0][1] + x0 * x0 * fit.xtx_inv[1][1];
+        let yhat = fit.coefs[0] + fit.coefs[1] * x0;
+        let mean_err = crit * se * h.sqrt();
+        let pred_err = crit * se * (1.0 + h).sqrt();
+        self.strings[4] =
+            Constr::In(fmt_num(yhat - mean_err), fmt_num(yhat + mean_err)).to_string();
+        self.strings[5] =
+            Constr::In(fmt_num(yhat - pred_err), fmt_num(yhat + pred_err)).to_string();
+
+        self.xs = xs;
+        self.ys = ys;
+        self.fitted = fit.fitted;
+        self.resid = fit.resid;
+        Ok(())
+    }
+}
 
-                self.strings[0] = self.pval.to_string();
-                self.strings[1].clear();
+impl Widget for &mut LinReg {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui
+            .horizontal(|ui| {
+                let resp = ui.data_box("x", &mut self.strings[0]);
+                resp.union(ui.data_box("y", &mut self.strings[1]))
+            })
+            .inner;
+        resp = resp.union(ui.num_box("x for intervals", &mut self.strings[2]));
+        resp = resp.union(ui.num_box("confidence", &mut self.strings[3]));
+        if resp.changed() {
+            self.summary.clear();
+            self.table.clear();
+            self.xs.clear();
+            self.strings[4].clear();
+            self.strings[5].clear();
+            self.strings[6] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
             };
-            f();
         }
-        ui.num_box("", &mut self.strings[0].clone());
-        ui.label(&self.strings[1]);
+        if !self.summary.is_empty() {
+            ui.table("linreg summary", &["", ""], &self.summary);
+        }
+        if !self.table.is_empty() {
+            ui.table(
+                "linreg coefs",
+                &["Term", "Estimate", "SE", "t", "p", "CI"],
+                &self.table,
+            );
+        }
+        ui.num_box("Mean response", &mut self.strings[4].clone());
+        ui.num_box("Prediction", &mut self.strings[5].clone());
+        ui.label(RichText::new(&self.strings[6]).color(Color32::DARK_RED));
+        if !self.xs.is_empty() {
+            let pts: Vec<[f64; 2]> = self
+                .xs
+                .iter()
+                .zip(self.ys.iter())
+                .map(|(x, y)| [*x, *y])
+                .collect();
+            let mut line: Vec<[f64; 2]> = self
+                .xs
+                .iter()
+                .zip(self.fitted.iter())
+                .map(|(x, y)| [*x, *y])
+                .collect();
+            line.sort_by(|a, b| a[0].total_cmp(&b[0]));
+            let res: Vec<[f64; 2]> = self
+                .fitted
+                .iter()
+                .zip(self.resid.iter())
+                .map(|(x, y)| [*x, *y])
+                .collect();
+            ui.horizontal(|ui| {
+                Plot::new("Scatter")
+                    .height(250.)
+                    .width(350.)
+                    .show(ui, |ui| {
+                        ui.points(Points::new(pts).radius(3.));
+                        ui.line(Line::new(line).color(Color32::RED));
+                    });
+                Plot::new("Residuals")
+                    .height(250.)
+                    .width(350.)
+                    .show(ui, |ui| {
+                        ui.points(Points::new(res).radius(3.));
+                        ui.hline(HLine::new(0.0).color(Color32::RED));
+                    });
+            });
+        }
         resp
     }
 }
 
-impl RCTable {
-    fn vfill(&mut self) {
-        for (sr, vr) in self.kstrings.iter().zip(self.sample_vals.iter_mut()) {
-            for (s, v) in sr.iter().zip(vr.iter_mut()) {
-                *v = match s.parse::<f32>() {
-                    Ok(v) => Some(v),
-                    Err(_) => None,
-                };
-            }
+#[derive(Clone)]
+pub(crate) struct MultiReg {
+    /// Name and data of each predictor
+    kstrings: Vec<(String, String)>,
+    fitted: Vec<f64>,
+    resid: Vec<f64>,
+    /// Standardized residuals against normal quantiles
+    qq: Vec<[f64; 2]>,
+    summary: Vec<Vec<String>>,
+    anova: Vec<Vec<String>>,
+    table: Vec<Vec<String>>,
+    flagged: Vec<Vec<String>>,
+    /// response, error, warning
+    strings: [String; 3],
+}
+
+impl Default for MultiReg {
+    fn default() -> Self {
+        Self {
+            kstrings: vec![
+                ("x1".to_string(), "".to_string()),
+                ("x2".to_string(), "".to_string()),
+            ],
+            fitted: vec![],
+            resid: vec![],
+            qq: vec![],
+            summary: vec![],
+            anova: vec![],
+            table: vec![],
+            flagged: vec![],
+            strings: ["".to_string(), "".to_string(), "".to_string()],
+        }
+    }
+}
+
+impl MultiReg {
+    fn calc(&mut self) -> Result<(), String> {
+        let y = parse_data(&self.strings[0])?;
+        let n = y.len();
+        let xs = self
+            .kstrings
+            .iter()
+            .map(|(name, d)| {
+                let col = parse_data(d).map_err(|e| format!("{name}: {e}"))?;
+                if col.len() != n {
+                    return Err(format!("{name} is not the same length as the response"));
+                }
+                Ok(col)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let k = xs.len();
+        if k == 0 {
+            return Err("Need at least one predictor".to_string());
+        }
+        if n < k + 2 {
+            return Err("Need more observations than parameters".to_string());
+        }
+
+        let cols: Vec<Vec<f64>> = std::iter::once(vec![1.0; n])
+            .chain(xs.iter().cloned())
+            .collect();
+        let fit = lstsq(&cols, &y).map_err(|e| format!("{e}, check for collinear predictors"))?;
+
+        let dfr = k as f64;
+        let dfe = (n - k - 1) as f64;
+        let ybar = y.iter().sum::<f64>() / n as f64;
+        let sst = y.iter().map(|y| (y - ybar).powi(2)).sum::<f64>();
+        let ssr = sst - fit.rss;
+        let mse = fit.rss / dfe;
+        if sst == 0.0 {
+            return Err(
+                "All responses are the same, R² is undefined and there is nothing to fit"
+                    .to_string(),
+            );
         }
+        let r2 = ssr / sst;
+        let adj = 1.0 - (1.0 - r2) * (n - 1) as f64 / dfe;
+        self.summary = vec![
+            vec!["R²".to_string(), fmt_num(r2)],
+            vec!["Adjusted R²".to_string(), fmt_num(adj)],
+            vec!["SE of estimate".to_string(), fmt_num(mse.sqrt())],
+        ];
+        self.anova = vec![
+            anova_row("Regression", dfr, ssr, mse, dfe),
+            vec![
+                "Residual".to_string(),
+                dfe.to_string(),
+                fmt_num(fit.rss),
+                fmt_num(mse),
+                "".to_string(),
+                "".to_string(),
+            ],
+            vec![
+                "Total".to_string(),
+                (n - 1).to_string(),
+                fmt_num(sst),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        ];
+
+        let vifs = (0..k)
+            .map(|j| {
+                if k == 1 {
+                    return Ok(1.0);
+                }
+                let others: Vec<Vec<f64>> = std::iter::once(vec![1.0; n])
+                    .chain(
+                        xs.iter()
+                            .enumerate()
+                            .filter(|(i, _)| *i != j)
+                            .map(|(_, c)| c.clone()),
+                    )
+                    .collect();
+                let m = xs[j].iter().sum::<f64>() / n as f64;
+                let ss = xs[j].iter().map(|x| (x - m).powi(2)).sum::<f64>();
+                Ok(ss / lstsq(&others, &xs[j])?.rss)
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        self.table = std::iter::once("Intercept")
+            .chain(self.kstrings.iter().map(|(n, _)| n.as_str()))
+            .enumerate()
+            .map(|(i, name)| {
+                let b = fit.coefs[i];
+                let sb = (mse * fit.xtx_inv[i][i]).sqrt();
+                let t = b / sb;
+                vec![
+                    name.to_string(),
+                    fmt_num(b),
+                    fmt_num(sb),
+                    fmt_num(t),
+                    fmt_num(t_pval(t, dfe)),
+                    if i == 0 {
+                        "".to_string()
+                    } else {
+                        fmt_num(vifs[i - 1])
+                    },
+                ]
+            })
+            .collect();
+
+        // Leverage is the diagonal of X (X'X)^-1 X'
+        let leverage: Vec<f64> = (0..n)
+            .map(|r| {
+                (0..=k)
+                    .map(|i| {
+                        (0..=k)
+                            .map(|j| cols[i][r] * fit.xtx_inv[i][j] * cols[j][r])
+                            .sum::<f64>()
+                    })
+                    .sum()
+            })
+            .collect();
+        // A point with leverage 1 is fit exactly whatever its y, so it has no residual scale
+        let std_resid: Vec<Option<f64>> = fit
+            .resid
+            .iter()
+            .zip(leverage.iter())
+            .map(|(e, h)| {
+                let scale = (mse * (1.0 - h)).sqrt();
+                (scale > 1e-12).then(|| e / scale)
+            })
+            .collect();
+        let high_lev = 2.0 * (k + 1) as f64 / n as f64;
+        self.flagged = std_resid
+            .iter()
+            .zip(leverage.iter())
+            .enumerate()
+            .filter(|(_, (r, h))| r.map_or(true, |r| r.abs() > 2.0) || **h > high_lev)
+            .map(|(i, (r, h))| {
+                let r = match r {
+                    Some(r) => fmt_num(*r),
+                    None if mse > 0.0 => "undefined, leverage 1".to_string(),
+                    None => "undefined, perfect fit".to_string(),
+                };
+                vec![
+                    (i + 1).to_string(),
+                    fmt_num(y[i]),
+                    fmt_num(fit.fitted[i]),
+                    r,
+                    fmt_num(*h),
+                ]
+            })
+            .collect();
+        if std_resid.iter().any(|r| r.is_none()) {
+            self.strings[2] = if mse > 0.0 {
+                "Some points have leverage 1, their studentized residuals are undefined".to_string()
+            } else {
+                "The fit is perfect, studentized residuals are undefined".to_string()
+            };
+        }
+
+        let mut sorted: Vec<f64> = std_resid.into_iter().flatten().collect();
+        sorted.sort_by(f64::total_cmp);
+        let snd = Normal::new(0.0, 1.0).expect("SND cant fail");
+        self.qq = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, r)| [snd.inverse_cdf((i as f64 + 0.5) / sorted.len() as f64), *r])
+            .collect();
+        self.fitted = fit.fitted;
+        self.resid = fit.resid;
+        Ok(())
+    }
+}
+
+impl Widget for &mut MultiReg {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let rba = ui.button("Add predictor");
+        if rba.clicked() {
+            self.kstrings
+                .push((format!("x{}", self.kstrings.len() + 1), "".to_string()));
+        }
+        let rbr = ui.button("Remove predictor");
+        if rbr.clicked() {
+            self.kstrings.pop();
+        }
+        let mut resp = rba.union(rbr);
+        resp = resp.union(
+            ui.horizontal(|ui| {
+                let mut resp = ui.data_box("Response", &mut self.strings[0]);
+                for (name, d) in self.kstrings.iter_mut() {
+                    resp = resp.union(
+                        ui.vertical(|ui| {
+                            let resp = ui.add_sized((100., 20.), TextEdit::singleline(name));
+                            resp.union(ui.data_box("", d))
+                        })
+                        .inner,
+                    );
+                }
+                resp
+            })
+            .inner,
+        );
+        if resp.changed() {
+            self.table.clear();
+            self.fitted.clear();
+            self.strings[2].clear();
+            self.strings[1] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.label(RichText::new(&self.strings[1]).color(Color32::DARK_RED));
+        if self.table.is_empty() {
+            return resp;
+        }
+        ui.label(RichText::new(&self.strings[2]).color(Color32::GOLD));
+        ui.table("multireg summary", &["", ""], &self.summary);
+        ui.table(
+            "multireg anova",
+            &["Source", "df", "SS", "MS", "F", "p"],
+            &self.anova,
+        );
+        ui.table(
+            "multireg coefs",
+            &["Term", "Estimate", "SE", "t", "p", "VIF"],
+            &self.table,
+        );
+        if !self.flagged.is_empty() {
+            ui.label("Large standardized residuals or high leverage");
+            ui.table(
+                "multireg flagged",
+                &["Obs", "y", "Fitted", "Std resid", "Leverage"],
+                &self.flagged,
+            );
+        }
+        let res: Vec<[f64; 2]> = self
+            .fitted
+            .iter()
+            .zip(self.resid.iter())
+            .map(|(x, y)| [*x, *y])
+            .collect();
+        let (lo, hi) = (
+            self.qq.first().map(|p| p[0]).unwrap_or(0.0),
+            self.qq.last().map(|p| p[0]).unwrap_or(0.0),
+        );
+        ui.horizontal(|ui| {
+            Plot::new("Residuals vs fitted")
+                .height(250.)
+                .width(350.)
+                .show(ui, |ui| {
+                    ui.points(Points::new(res).radius(3.));
+                    ui.hline(HLine::new(0.0).color(Color32::RED));
+                });
+            Plot::new("Normal QQ")
+                .height(250.)
+                .width(350.)
+                .show(ui, |ui| {
+                    ui.points(Points::new(self.qq.clone()).radius(3.));
+                    ui.line(Line::new(vec![[lo, lo], [hi, hi]]).color(Color32::RED));
+                });
+        });
+        resp
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum GofMode {
+    #[default]
+    Proportions,
+    Poisson,
+    Binomial,
+    Normal,
+}
+
+#[derive(Clone)]
+pub(crate) struct GoodnessOfFit {
+    mode: GofMode,
+    table: Vec<Vec<String>>,
+    /// categories, observed, proportions, param 1, param 2, statistic, df, p, warning, error
+    strings: [String; 10],
+}
+
+impl Default for GoodnessOfFit {
+    fn default() -> Self {
+        Self {
+            mode: GofMode::Proportions,
+            table: vec![],
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+/// Reads an optional parameter, blank means it should be estimated.
+fn opt_param(s: &str, name: &str) -> Result<Option<f64>, String> {
+    if s.trim().is_empty() {
+        return Ok(None);
+    }
+    s.parse::<Expr>()
+        .ok()
+        .and_then(|e| e.eval().ok())
+        .map(Some)
+        .ok_or(format!("{name} is invalid"))
+}
+
+/// Probabilities of discrete categories, with the first and last taking in the tails.
+fn tail_probs(cats: &[f64], pmf: impl Fn(u64) -> f64, cdf: impl Fn(u64) -> f64) -> Vec<f64> {
+    let k = cats.len();
+    cats.iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let c = *c as u64;
+            if i == 0 {
+                cdf(c)
+            } else if i == k - 1 {
+                1.0 - cdf(c) + pmf(c)
+            } else {
+                pmf(c)
+            }
+        })
+        .collect()
+}
+
+impl GoodnessOfFit {
+    fn calc(&mut self) -> Result<(), String> {
+        let obs = parse_data(&self.strings[1])?;
+        let k = obs.len();
+        if k < 2 {
+            return Err("Need at least 2 categories".to_string());
+        }
+        let n = obs.iter().sum::<f64>();
+        let (names, probs, estimated): (Vec<String>, Vec<f64>, usize) = match self.mode {
+            GofMode::Proportions => {
+                let props = parse_data(&self.strings[2])?;
+                if props.len() != k {
+                    return Err("Need a proportion for every category".to_string());
+                }
+                if props.iter().any(|p| *p < 0.0) {
+                    return Err("Proportions can't be negative".to_string());
+                }
+                if (props.iter().sum::<f64>() - 1.0).abs() > 1e-6 {
+                    return Err("Proportions must sum to 1".to_string());
+                }
+                let mut names = parse_labels(&self.strings[0]);
+                names.extend((names.len()..k).map(|i| (i + 1).to_string()));
+                (names, props, 0)
+            }
+            GofMode::Poisson | GofMode::Binomial => {
+                let cats = parse_data(&self.strings[0])?;
+                if cats.len() != k {
+                    return Err("Need a value for every category".to_string());
+                }
+                // tail_probs gives each middle value its own pmf, so a gap would lose probability
+                if cats[0] < 0.0
+                    || cats.iter().any(|c| c.fract() != 0.0)
+                    || cats.windows(2).any(|w| w[1] != w[0] + 1.0)
+                {
+                    return Err(
+                        "Values must be consecutive whole numbers from smallest to largest"
+                            .to_string(),
+                    );
+                }
+                let mean = cats.iter().zip(obs.iter()).map(|(c, o)| c * o).sum::<f64>() / n;
+                let (probs, estimated) = if self.mode == GofMode::Poisson {
+                    let (l, e) = match opt_param(&self.strings[3], "λ")? {
+                        Some(l) => (l, 0),
+                        None => (mean, 1),
+                    };
+                    let d = Poisson::new(l).map_err(|e| e.to_string())?;
+                    (tail_probs(&cats, |x| d.pmf(x), |x| d.cdf(x)), e)
+                } else {
+                    let trials = opt_param(&self.strings[4], "Trials")?
+                        .ok_or("Number of trials must be set")?;
+                    if trials.fract() != 0.0 || trials < cats[k - 1] {
+                        return Err(
+                            "Trials must be a whole number at least as large as every value"
+                                .to_string(),
+                        );
+                    }
+                    let (p, e) = match opt_param(&self.strings[3], "p")? {
+                        Some(p) => (p, 0),
+                        None => (mean / trials, 1),
+                    };
+                    let d = Binomial::new(p, trials as u64).map_err(|e| e.to_string())?;
+                    (tail_probs(&cats, |x| d.pmf(x), |x| d.cdf(x)), e)
+                };
+                let names = cats
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        if i == 0 {
+                            format!("<={c}")
+                        } else if i == k - 1 {
+                            format!(">={c}")
+                        } else {
+                            c.to_string()
+                        }
+                    })
+                    .collect();
+                (names, probs, estimated)
+            }
+            GofMode::Normal => {
+                let edges = parse_data(&self.strings[0])?;
+                // The last bin takes in everything above the bound before it
+                if edges.len() != k - 1 {
+                    return Err("Need an upper bound for every bin but the last".to_string());
+                }
+                if k < 3 {
+                    return Err("Need at least 3 bins".to_string());
+                }
+                if edges.windows(2).any(|w| w[1] <= w[0]) {
+                    return Err("Bin bounds must be increasing".to_string());
+                }
+                let mids: Vec<f64> = (0..k)
+                    .map(|i| {
+                        if i == 0 {
+                            edges[0] - (edges[1] - edges[0]) / 2.0
+                        } else if i == k - 1 {
+                            edges[k - 2] + (edges[k - 2] - edges[k - 3]) / 2.0
+                        } else {
+                            (edges[i - 1] + edges[i]) / 2.0
+                        }
+                    })
+                    .collect();
+                let mut estimated = 0;
+                let mean = match opt_param(&self.strings[3], "Mean")? {
+                    Some(m) => m,
+                    None => {
+                        estimated += 1;
+                        mids.iter().zip(obs.iter()).map(|(m, o)| m * o).sum::<f64>() / n
+                    }
+                };
+                let sd = match opt_param(&self.strings[4], "SD")? {
+                    Some(s) => s,
+                    None => {
+                        estimated += 1;
+                        (mids
+                            .iter()
+                            .zip(obs.iter())
+                            .map(|(m, o)| o * (m - mean).powi(2))
+                            .sum::<f64>()
+                            / (n - 1.0))
+                            .sqrt()
+                    }
+                };
+                let d = Normal::new(mean, sd).map_err(|e| e.to_string())?;
+                let probs = (0..k)
+                    .map(|i| {
+                        let hi = if i == k - 1 { 1.0 } else { d.cdf(edges[i]) };
+                        let lo = if i == 0 { 0.0 } else { d.cdf(edges[i - 1]) };
+                        hi - lo
+                    })
+                    .collect();
+                let names = (0..k)
+                    .map(|i| {
+                        if i == 0 {
+                            format!("<={}", edges[0])
+                        } else if i == k - 1 {
+                            format!(">{}", edges[k - 2])
+                        } else {
+                            format!("]{},{}]", edges[i - 1], edges[i])
+                        }
+                    })
+                    .collect();
+                (names, probs, estimated)
+            }
+        };
+
+        if k < 2 + estimated {
+            return Err("Not enough categories for the estimated parameters".to_string());
+        }
+        let exp: Vec<f64> = probs.iter().map(|p| p * n).collect();
+        if let Some(i) = exp.iter().position(|e| *e <= 0.0) {
+            return Err(format!(
+                "Category {} has no expected count, merge it with a neighbor",
+                names[i]
+            ));
+        }
+        let contrib: Vec<f64> = obs
+            .iter()
+            .zip(exp.iter())
+            .map(|(o, e)| (o - e).powi(2) / e)
+            .collect();
+        let stat = contrib.iter().sum::<f64>();
+        let df = (k - 1 - estimated) as f64;
+        let chi = ChiSquared::new(df).map_err(|e| e.to_string())?;
+
+        self.table = (0..k)
+            .map(|i| {
+                vec![
+                    names[i].clone(),
+                    fmt_num(obs[i]),
+                    fmt_num(exp[i]),
+                    fmt_num(contrib[i]),
+                ]
+            })
+            .collect();
+        self.strings[5] = stat.to_string();
+        self.strings[6] = df.to_string();
+        self.strings[7] = (1.0 - chi.cdf(stat)).to_string();
+        let small = exp.iter().filter(|e| **e < 5.0).count();
+        self.strings[8] = if small > 0 {
+            format!("{small} expected counts are below 5, the chi square approximation may be poor")
+        } else {
+            "".to_string()
+        };
+        Ok(())
+    }
+}
+
+impl Widget for &mut GoodnessOfFit {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mode_changed = ui
+            .horizontal(|ui| {
+                let mut changed = false;
+                for (m, name) in [
+                    (GofMode::Proportions, "Given proportions"),
+                    (GofMode::Poisson, "Poisson"),
+                    (GofMode::Binomial, "Binomial"),
+                    (GofMode::Normal, "Normal"),
+                ] {
+                    changed |= ui.radio_value(&mut self.mode, m, name).changed();
+                }
+                changed
+            })
+            .inner;
+        let cats = match self.mode {
+            GofMode::Proportions => "Category",
+            GofMode::Poisson | GofMode::Binomial => "Value",
+            GofMode::Normal => "Bin upper bound",
+        };
+        let mut resp = ui
+            .horizontal(|ui| {
+                let mut resp = ui.data_box(cats, &mut self.strings[0]);
+                resp = resp.union(ui.data_box("Observed", &mut self.strings[1]));
+                if self.mode == GofMode::Proportions {
+                    resp = resp.union(ui.data_box("Proportion", &mut self.strings[2]));
+                }
+                resp
+            })
+            .inner;
+        match self.mode {
+            GofMode::Proportions => {}
+            GofMode::Poisson => {
+                resp = resp.union(ui.num_box("λ (blank to estimate)", &mut self.strings[3]));
+            }
+            GofMode::Binomial => {
+                resp = resp.union(ui.num_box("p (blank to estimate)", &mut self.strings[3]));
+                resp = resp.union(ui.num_box("trials", &mut self.strings[4]));
+            }
+            GofMode::Normal => {
+                ui.label("Leave the last bin's upper bound blank, it holds everything above");
+                resp = resp.union(ui.num_box("mean (blank to estimate)", &mut self.strings[3]));
+                resp = resp.union(ui.num_box("sd (blank to estimate)", &mut self.strings[4]));
+            }
+        }
+        if mode_changed {
+            resp.mark_changed();
+        }
+        if resp.changed() {
+            self.table.clear();
+            for s in self.strings[5..9].iter_mut() {
+                s.clear();
+            }
+            self.strings[9] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        if !self.table.is_empty() {
+            ui.table(
+                "gof",
+                &["Category", "Observed", "Expected", "Contribution"],
+                &self.table,
+            );
+        }
+        ui.num_box("Chi square", &mut self.strings[5].clone());
+        ui.num_box("df", &mut self.strings[6].clone());
+        ui.num_box("p", &mut self.strings[7].clone());
+        ui.label(RichText::new(&self.strings[8]).color(Color32::GOLD));
+        ui.label(RichText::new(&self.strings[9]).color(Color32::DARK_RED));
+        resp
+    }
+}
+
+pub(crate) trait AltRadio {
+    fn alt_radio(&mut self, alt: &mut Alternative) -> bool;
+}
+
+impl AltRadio for Ui {
+    fn alt_radio(&mut self, alt: &mut Alternative) -> bool {
+        self.horizontal(|ui| {
+            ui.label("H1");
+            let mut changed = false;
+            for (a, name) in [
+                (Alternative::TwoSided, "two sided"),
+                (Alternative::Less, "less"),
+                (Alternative::Greater, "greater"),
+            ] {
+                changed |= ui.radio_value(alt, a, name).changed();
+            }
+            changed
+        })
+        .inner
+    }
+}
+
+/// Average ranks starting at 1, with the sizes of any groups of ties.
+fn ranks(v: &[f64]) -> (Vec<f64>, Vec<usize>) {
+    let mut idx: Vec<usize> = (0..v.len()).collect();
+    idx.sort_by(|a, b| v[*a].total_cmp(&v[*b]));
+    let mut r = vec![0.0; v.len()];
+    let mut ties = vec![];
+    let mut i = 0;
+    while i < idx.len() {
+        let mut j = i;
+        while j + 1 < idx.len() && v[idx[j + 1]] == v[idx[i]] {
+            j += 1;
+        }
+        let avg = (i + j) as f64 / 2.0 + 1.0;
+        for k in &idx[i..=j] {
+            r[*k] = avg;
+        }
+        if j > i {
+            ties.push(j - i + 1);
+        }
+        i = j + 1;
+    }
+    (r, ties)
+}
+
+/// Sum of t^3 - t over tie groups.
+fn tie_sum(ties: &[usize]) -> f64 {
+    ties.iter().map(|t| (t.pow(3) - t) as f64).sum()
+}
+
+/// Lower and upper tails of a distribution of counts, indexed by twice the statistic.
+fn count_tails(counts: &[f64], t2: usize) -> (f64, f64) {
+    let total: f64 = counts.iter().sum();
+    let lower = counts[..=t2.min(counts.len() - 1)].iter().sum::<f64>() / total;
+    let upper = counts[t2.min(counts.len())..].iter().sum::<f64>() / total;
+    (lower.min(1.0), upper.min(1.0))
+}
+
+/// Lower and upper tails of a normal approximation, with a continuity correction.
+fn normal_tails(t: f64, mean: f64, sd: f64, corr: f64) -> (f64, f64, f64) {
+    let snd = Normal::new(0.0, 1.0).expect("SND cant fail");
+    let z = (t - mean) / sd;
+    (
+        z,
+        snd.cdf((t - mean + corr) / sd),
+        1.0 - snd.cdf((t - mean - corr) / sd),
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum NpTest {
+    #[default]
+    MannWhitney,
+    SignedRank,
+    KruskalWallis,
+    Sign,
+}
+
+/// Most samples that get an exact distribution.
+const EXACT_MAX: usize = 50;
+/// Most arrangements enumerated for an exact Kruskal-Wallis test.
+const EXACT_KW_MAX: f64 = 200_000.0;
+
+#[derive(Clone)]
+pub(crate) struct NonParam {
+    test: NpTest,
+    alt: Alternative,
+    rank_header: Vec<&'static str>,
+    ranks: Vec<Vec<String>>,
+    results: Vec<Vec<String>>,
+    /// first column, second column, hypothesized median, error
+    strings: [String; 4],
+}
+
+impl Default for NonParam {
+    fn default() -> Self {
+        Self::new(NpTest::MannWhitney)
+    }
+}
+
+impl NonParam {
+    fn new(test: NpTest) -> Self {
+        Self {
+            test,
+            alt: Alternative::TwoSided,
+            rank_header: vec![],
+            ranks: vec![],
+            results: vec![],
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "0".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+
+    fn calc(&mut self) -> Result<(), String> {
+        match self.test {
+            NpTest::MannWhitney => self.mann_whitney(),
+            NpTest::SignedRank | NpTest::Sign => self.paired(),
+            NpTest::KruskalWallis => self.kruskal_wallis(),
+        }
+    }
+
+    fn mann_whitney(&mut self) -> Result<(), String> {
+        let x = parse_data(&self.strings[0])?;
+        let y = parse_data(&self.strings[1])?;
+        let (n1, n2) = (x.len(), y.len());
+        if n1 == 0 || n2 == 0 {
+            return Err("Both samples need values".to_string());
+        }
+        let all: Vec<f64> = x.iter().chain(y.iter()).cloned().collect();
+        let n = all.len();
+        let (r, ties) = ranks(&all);
+        let w: f64 = r[..n1].iter().sum();
+        let u = w - (n1 * (n1 + 1)) as f64 / 2.0;
+
+        self.rank_header = vec!["Value", "Sample", "Rank"];
+        self.ranks = all
+            .iter()
+            .zip(r.iter())
+            .enumerate()
+            .map(|(i, (v, r))| {
+                vec![
+                    v.to_string(),
+                    if i < n1 { "1" } else { "2" }.to_string(),
+                    r.to_string(),
+                ]
+            })
+            .collect();
+        self.ranks.sort_by(|a, b| {
+            a[2].parse::<f64>()
+                .unwrap_or(0.0)
+                .total_cmp(&b[2].parse().unwrap_or(0.0))
+        });
+
+        let (lower, upper, method) = if n <= EXACT_MAX {
+            // Ways to pick n1 of the doubled ranks with each sum
+            let max = r.iter().map(|r| (2.0 * r) as usize).sum::<usize>();
+            let mut dp = vec![vec![0.0; max + 1]; n1 + 1];
+            dp[0][0] = 1.0;
+            for rank in r.iter().map(|r| (2.0 * r) as usize) {
+                for k in (1..=n1).rev() {
+                    for s in (rank..=max).rev() {
+                        dp[k][s] += dp[k - 1][s - rank];
+                    }
+                }
+            }
+            let (l, u) = count_tails(&dp[n1], (2.0 * w) as usize);
+            (l, u, "exact".to_string())
+        } else {
+            let mean = (n1 * (n + 1)) as f64 / 2.0;
+            let var =
+                (n1 * n2) as f64 / 12.0 * ((n + 1) as f64 - tie_sum(&ties) / (n * (n - 1)) as f64);
+            let (z, l, u) = normal_tails(w, mean, var.sqrt(), 0.5);
+            (l, u, format!("normal, z = {}", fmt_num(z)))
+        };
+        self.results = vec![
+            vec!["W (rank sum of 1)".to_string(), w.to_string()],
+            vec!["U".to_string(), u.to_string()],
+            vec!["Method".to_string(), method],
+            vec!["p".to_string(), fmt_num(self.alt.pval(lower, upper))],
+        ];
+        Ok(())
+    }
+
+    fn paired(&mut self) -> Result<(), String> {
+        let x = parse_data(&self.strings[0])?;
+        let y = if self.strings[1].trim().is_empty() {
+            vec![0.0; x.len()]
+        } else {
+            parse_data(&self.strings[1])?
+        };
+        if x.len() != y.len() {
+            return Err("Paired samples must be the same length".to_string());
+        }
+        let m0 = self.strings[2]
+            .parse::<Expr>()
+            .ok()
+            .and_then(|e| e.eval().ok())
+            .ok_or("Hypothesized median is invalid")?;
+        let d: Vec<f64> = x.iter().zip(y.iter()).map(|(x, y)| x - y - m0).collect();
+        let nz: Vec<f64> = d.iter().cloned().filter(|d| *d != 0.0).collect();
+        let n = nz.len();
+        if n == 0 {
+            return Err("Every difference is zero".to_string());
+        }
+
+        if self.test == NpTest::Sign {
+            let pos = nz.iter().filter(|d| **d > 0.0).count();
+            self.rank_header = vec!["x", "y", "Difference", "Sign"];
+            self.ranks = x
+                .iter()
+                .zip(y.iter())
+                .zip(d.iter())
+                .map(|((x, y), d)| {
+                    vec![
+                        x.to_string(),
+                        y.to_string(),
+                        d.to_string(),
+                        if *d > 0.0 {
+                            "+"
+                        } else if *d < 0.0 {
+                            "-"
+                        } else {
+                            "0"
+                        }
+                        .to_string(),
+                    ]
+                })
+                .collect();
+            let bin = Binomial::new(0.5, n as u64).expect("p of 0.5 is valid");
+            let lower = bin.cdf(pos as u64);
+            let upper = 1.0 - lower + bin.pmf(pos as u64);
+            let (z, nl, nu) =
+                normal_tails(pos as f64, n as f64 / 2.0, (n as f64).sqrt() / 2.0, 0.5);
+            self.results = vec![
+                vec!["Positive".to_string(), pos.to_string()],
+                vec!["Negative".to_string(), (n - pos).to_string()],
+                vec!["Zeros dropped".to_string(), (d.len() - n).to_string()],
+                vec!["p exact".to_string(), fmt_num(self.alt.pval(lower, upper))],
+                vec![
+                    format!("p normal, z = {}", fmt_num(z)),
+                    fmt_num(self.alt.pval(nl, nu)),
+                ],
+            ];
+            return Ok(());
+        }
+
+        let abs: Vec<f64> = nz.iter().map(|d| d.abs()).collect();
+        let (r, ties) = ranks(&abs);
+        let wp: f64 = r
+            .iter()
+            .zip(nz.iter())
+            .filter(|(_, d)| **d > 0.0)
+            .map(|(r, _)| r)
+            .sum();
+        let wm = (n * (n + 1)) as f64 / 2.0 - wp;
+
+        self.rank_header = vec!["Difference", "|Difference|", "Rank", "Signed rank"];
+        let mut rows: Vec<(f64, Vec<String>)> = nz
+            .iter()
+            .zip(r.iter())
+            .map(|(d, r)| {
+                (
+                    *r,
+                    vec![
+                        d.to_string(),
+                        d.abs().to_string(),
+                        r.to_string(),
+                        (r * d.signum()).to_string(),
+                    ],
+                )
+            })
+            .collect();
+        rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.ranks = rows.into_iter().map(|(_, r)| r).collect();
+
+        let (lower, upper, method) = if n <= EXACT_MAX {
+            // Ways each sign assignment of the doubled ranks reaches each positive sum
+            let max = r.iter().map(|r| (2.0 * r) as usize).sum::<usize>();
+            let mut dp = vec![0.0; max + 1];
+            dp[0] = 1.0;
+            for rank in r.iter().map(|r| (2.0 * r) as usize) {
+                for s in (rank..=max).rev() {
+                    dp[s] += dp[s - rank];
+                }
+            }
+            let (l, u) = count_tails(&dp, (2.0 * wp) as usize);
+            (l, u, "exact".to_string())
+        } else {
+            let nf = n as f64;
+            let mean = nf * (nf + 1.0) / 4.0;
+            let var = nf * (nf + 1.0) * (2.0 * nf + 1.0) / 24.0 - tie_sum(&ties) / 48.0;
+            let (z, l, u) = normal_tails(wp, mean, var.sqrt(), 0.5);
+            (l, u, format!("normal, z = {}", fmt_num(z)))
+        };
+        self.results = vec![
+            vec!["W+".to_string(), wp.to_string()],
+            vec!["W-".to_string(), wm.to_string()],
+            vec!["Zeros dropped".to_string(), (d.len() - n).to_string()],
+            vec!["Method".to_string(), method],
+            vec!["p".to_string(), fmt_num(self.alt.pval(lower, upper))],
+        ];
+        Ok(())
+    }
+
+    fn kruskal_wallis(&mut self) -> Result<(), String> {
+        let y = parse_data(&self.strings[0])?;
+        let g = parse_labels(&self.strings[1]);
+        if y.len() != g.len() {
+            return Err("Response and groups must be the same length".to_string());
+        }
+        let (names, idx) = levels(&g);
+        let k = names.len();
+        if k < 2 {
+            return Err("Need at least 2 groups".to_string());
+        }
+        if y.iter().all(|v| *v == y[0]) {
+            return Err("Every value is tied".to_string());
+        }
+        let n = y.len();
+        let (r, ties) = ranks(&y);
+        let mut sizes = vec![0usize; k];
+        let mut sums = vec![0.0; k];
+        for (i, r) in idx.iter().zip(r.iter()) {
+            sizes[*i] += 1;
+            sums[*i] += r;
+        }
+        let nf = n as f64;
+        let corr = 1.0 - tie_sum(&ties) / (nf * nf * nf - nf);
+        let h_of = |sums: &[f64]| {
+            (12.0 / (nf * (nf + 1.0))
+                * sums
+                    .iter()
+                    .zip(sizes.iter())
+                    .map(|(s, n)| s * s / *n as f64)
+                    .sum::<f64>()
+                - 3.0 * (nf + 1.0))
+                / corr
+        };
+        let h = h_of(&sums);
+
+        self.rank_header = vec!["Value", "Group", "Rank"];
+        let mut rows: Vec<(f64, Vec<String>)> = y
+            .iter()
+            .zip(idx.iter())
+            .zip(r.iter())
+            .map(|((v, i), r)| (*r, vec![v.to_string(), names[*i].clone(), r.to_string()]))
+            .collect();
+        rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.ranks = rows.into_iter().map(|(_, r)| r).collect();
+
+        let arrangements = (function::factorial::ln_factorial(n as u64)
+            - sizes
+                .iter()
+                .map(|s| function::factorial::ln_factorial(*s as u64))
+                .sum::<f64>())
+        .exp();
+        let (p, method) = if arrangements <= EXACT_KW_MAX {
+            // Walk every way of splitting the ranks into groups of these sizes
+            fn walk(
+                r: &[f64],
+                left: &mut [usize],
+                sums: &mut [f64],
+                h_of: &dyn Fn(&[f64]) -> f64,
+                h: f64,
+                count: &mut (f64, f64),
+            ) {
+                let Some((first, rest)) = r.split_first() else {
+                    count.1 += 1.0;
+                    if h_of(sums) >= h - 1e-9 {
+                        count.0 += 1.0;
+                    }
+                    return;
+                };
+                for g in 0..left.len() {
+                    if left[g] > 0 {
+                        left[g] -= 1;
+                        sums[g] += first;
+                        walk(rest, left, sums, h_of, h, count);
+                        sums[g] -= first;
+                        left[g] += 1;
+                    }
+                }
+            }
+            let mut count = (0.0, 0.0);
+            walk(
+                &r,
+                &mut sizes.clone(),
+                &mut vec![0.0; k],
+                &h_of,
+                h,
+                &mut count,
+            );
+            (count.0 / count.1, "exact".to_string())
+        } else {
+            let chi = ChiSquared::new((k - 1) as f64).map_err(|e| e.to_string())?;
+            (1.0 - chi.cdf(h), "chi square".to_string())
+        };
+
+        self.results = names
+            .iter()
+            .zip(sizes.iter().zip(sums.iter()))
+            .map(|(name, (n, s))| vec![format!("{name}: n = {n}, rank sum"), s.to_string()])
+            .chain([
+                vec!["H (tie corrected)".to_string(), fmt_num(h)],
+                vec!["df".to_string(), (k - 1).to_string()],
+                vec!["Method".to_string(), method],
+                vec!["p".to_string(), fmt_num(p)],
+            ])
+            .collect();
+        Ok(())
+    }
+}
+
+impl Widget for &mut NonParam {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let (a, b) = match self.test {
+            NpTest::MannWhitney => ("Sample 1", "Sample 2"),
+            NpTest::SignedRank | NpTest::Sign => ("x", "y (blank for one sample)"),
+            NpTest::KruskalWallis => ("Response", "Group"),
+        };
+        let mut resp = ui
+            .horizontal(|ui| {
+                let resp = ui.data_box(a, &mut self.strings[0]);
+                resp.union(ui.data_box(b, &mut self.strings[1]))
+            })
+            .inner;
+        if matches!(self.test, NpTest::SignedRank | NpTest::Sign) {
+            resp = resp.union(ui.num_box("Hypothesized median difference", &mut self.strings[2]));
+        }
+        if self.test != NpTest::KruskalWallis && ui.alt_radio(&mut self.alt) {
+            resp.mark_changed();
+        }
+        if resp.changed() {
+            self.ranks.clear();
+            self.results.clear();
+            self.strings[3] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        if !self.results.is_empty() {
+            ui.table("np results", &["", ""], &self.results);
+            ui.table("np ranks", &self.rank_header, &self.ranks);
+        }
+        ui.label(RichText::new(&self.strings[3]).color(Color32::DARK_RED));
+        resp
+    }
+}
+
+pub(crate) fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let mx = x.iter().sum::<f64>() / n;
+    let my = y.iter().sum::<f64>() / n;
+    let sxy: f64 = x.iter().zip(y).map(|(x, y)| (x - mx) * (y - my)).sum();
+    let sxx: f64 = x.iter().map(|x| (x - mx).powi(2)).sum();
+    let syy: f64 = y.iter().map(|y| (y - my).powi(2)).sum();
+    sxy / (sxx * syy).sqrt()
+}
+
+/// Calls f with every ordering of v, by Heap's algorithm.
+pub(crate) fn for_each_permutation(v: &mut [f64], mut f: impl FnMut(&[f64])) {
+    let n = v.len();
+    let mut c = vec![0; n];
+    f(v);
+    let mut i = 0;
+    while i < n {
+        if c[i] < i {
+            v.swap(if i % 2 == 0 { 0 } else { c[i] }, i);
+            f(v);
+            c[i] += 1;
+            i = 0;
+        } else {
+            c[i] = 0;
+            i += 1;
+        }
+    }
+}
+
+/// Calls f with every way of picking k of the indices below n, each in increasing order.
+pub(crate) fn for_each_combination(n: usize, k: usize, mut f: impl FnMut(&[usize])) {
+    let mut idx: Vec<usize> = (0..k).collect();
+    loop {
+        f(&idx);
+        let Some(i) = (0..k).rev().find(|&i| idx[i] < n - k + i) else {
+            return;
+        };
+        idx[i] += 1;
+        for j in i + 1..k {
+            idx[j] = idx[j - 1] + 1;
+        }
+    }
+}
+
+/// Lower and upper tails of a t test on a correlation coefficient.
+fn corr_t_tails(r: f64, n: usize) -> Result<(f64, f64, f64), String> {
+    let df = n as f64 - 2.0;
+    let t = r * (df / (1.0 - r * r)).sqrt();
+    if !t.is_finite() {
+        return Err(
+            "The t statistic is undefined, r must be strictly between -1 and 1".to_string(),
+        );
+    }
+    let dist = StudentsT::new(0.0, 1.0, df).map_err(|e| e.to_string())?;
+    Ok((t, dist.cdf(t), 1.0 - dist.cdf(t)))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum CorrMethod {
+    Pearson,
+    #[default]
+    Spearman,
+    Kendall,
+}
+
+/// Most pairs that get an exact Spearman permutation test.
+const EXACT_SPEARMAN_MAX: usize = 9;
+
+#[derive(Clone, Default)]
+pub(crate) struct RankCorr {
+    method: CorrMethod,
+    alt: Alternative,
+    /// Name and data of each column
+    kstrings: Vec<(String, String)>,
+    names: Vec<String>,
+    matrix: Vec<Vec<f64>>,
+    tests: Vec<Vec<String>>,
+    rank_header: Vec<String>,
+    ranks: Vec<Vec<String>>,
+    /// error
+    strings: [String; 1],
+}
+
+impl RankCorr {
+    /// Coefficient, statistic description, method and tails for one pair.
+    fn test(&self, x: &[f64], y: &[f64]) -> Result<(f64, String, String, f64, f64), String> {
+        let n = x.len();
+        let (rx, tx) = ranks(x);
+        let (ry, ty) = ranks(y);
+        match self.method {
+            CorrMethod::Pearson => {
+                let r = pearson(x, y);
+                let (t, l, u) = corr_t_tails(r, n)?;
+                Ok((r, format!("t = {}", fmt_num(t)), "t".to_string(), l, u))
+            }
+            CorrMethod::Spearman => {
+                let r = pearson(&rx, &ry);
+                if n <= EXACT_SPEARMAN_MAX {
+                    // Every ordering of the y ranks
+                    let obs: f64 = rx.iter().zip(ry.iter()).map(|(a, b)| a * b).sum();
+                    let mut p = ry.clone();
+                    let (mut lower, mut upper, mut total) = (0.0, 0.0, 0.0);
+                    let tally = |p: &[f64]| {
+                        let s: f64 = rx.iter().zip(p).map(|(a, b)| a * b).sum();
+                        total += 1.0;
+                        if s <= obs + 1e-9 {
+                            lower += 1.0;
+                        }
+                        if s >= obs - 1e-9 {
+                            upper += 1.0;
+                        }
+                    };
+                    for_each_permutation(&mut p, tally);
+                    Ok((
+                        r,
+                        format!(
+                            "S = {}",
+                            rx.iter()
+                                .zip(ry.iter())
+                                .map(|(a, b)| (a - b).powi(2))
+                                .sum::<f64>()
+                        ),
+                        "exact".to_string(),
+                        lower / total,
+                        upper / total,
+                    ))
+                } else {
+                    let (t, l, u) = corr_t_tails(r, n)?;
+                    Ok((r, format!("t = {}", fmt_num(t)), "t".to_string(), l, u))
+                }
+            }
+            CorrMethod::Kendall => {
+                let mut s = 0.0;
+                for i in 0..n {
+                    for j in i + 1..n {
+                        s += ((x[i] - x[j]) * (y[i] - y[j])).signum();
+                    }
+                }
+                let n0 = (n * (n - 1)) as f64 / 2.0;
+                let pairs = |t: &[usize]| t.iter().map(|t| (t * (t - 1)) as f64 / 2.0).sum::<f64>();
+                let tau = s / ((n0 - pairs(&tx)) * (n0 - pairs(&ty))).sqrt();
+                if tx.is_empty() && ty.is_empty() && n <= EXACT_MAX {
+                    // Permutations of n by number of inversions
+                    let mut dp = vec![1.0];
+                    for k in 1..n {
+                        let mut next = vec![0.0; dp.len() + k];
+                        for (d, c) in dp.iter().enumerate() {
+                            for v in &mut next[d..=d + k] {
+                                *v += c;
+                            }
+                        }
+                        dp = next;
+                    }
+                    // Fewer discordant pairs is a larger S
+                    let d = ((n0 - s) / 2.0).round() as usize;
+                    let total: f64 = dp.iter().sum();
+                    let upper = dp[..=d].iter().sum::<f64>() / total;
+                    let lower = dp[d..].iter().sum::<f64>() / total;
+                    Ok((tau, format!("S = {s}"), "exact".to_string(), lower, upper))
+                } else {
+                    let nf = n as f64;
+                    let f = |t: &[usize], g: &dyn Fn(f64) -> f64| {
+                        t.iter().map(|t| g(*t as f64)).sum::<f64>()
+                    };
+                    let v0 = |t: f64| t * (t - 1.0) * (2.0 * t + 5.0);
+                    let v1 = |t: f64| t * (t - 1.0);
+                    let v2 = |t: f64| t * (t - 1.0) * (t - 2.0);
+                    let var = (v0(nf) - f(&tx, &v0) - f(&ty, &v0)) / 18.0
+                        + f(&tx, &v2) * f(&ty, &v2) / (9.0 * v2(nf))
+                        + f(&tx, &v1) * f(&ty, &v1) / (2.0 * v1(nf));
+                    let (z, l, u) = normal_tails(s, 0.0, var.sqrt(), 0.0);
+                    Ok((
+                        tau,
+                        format!("z = {}", fmt_num(z)),
+                        "normal".to_string(),
+                        l,
+                        u,
+                    ))
+                }
+            }
+        }
+    }
+
+    fn calc(&mut self) -> Result<(), String> {
+        if self.kstrings.len() < 2 {
+            return Err("Need at least 2 columns".to_string());
+        }
+        let cols = self
+            .kstrings
+            .iter()
+            .map(|(_, d)| parse_data(d))
+            .collect::<Result<Vec<_>, _>>()?;
+        let n = cols[0].len();
+        if cols.iter().any(|c| c.len() != n) {
+            return Err("Columns must be the same length".to_string());
+        }
+        if n < 3 {
+            return Err("Need at least 3 observations".to_string());
+        }
+        let names: Vec<String> = self.kstrings.iter().map(|(n, _)| n.clone()).collect();
+        if let Some(i) = cols.iter().position(|c| c.iter().all(|v| *v == c[0])) {
+            return Err(format!(
+                "Every value of {} is the same, so its correlation is undefined",
+                names[i]
+            ));
+        }
+        let k = cols.len();
+        let mut matrix = vec![vec![1.0; k]; k];
+        let mut tests = vec![];
+        for i in 0..k {
+            for j in i + 1..k {
+                let (r, stat, method, l, u) = self.test(&cols[i], &cols[j])?;
+                matrix[i][j] = r;
+                matrix[j][i] = r;
+                tests.push(vec![
+                    names[i].clone(),
+                    names[j].clone(),
+                    fmt_num(r),
+                    stat,
+                    method,
+                    fmt_num(self.alt.pval(l, u)),
+                ]);
+            }
+        }
+        let col_ranks: Vec<Vec<f64>> = cols.iter().map(|c| ranks(c).0).collect();
+        self.rank_header = names
+            .iter()
+            .flat_map(|n| [n.clone(), format!("{n} rank")])
+            .collect();
+        self.ranks = (0..n)
+            .map(|r| {
+                cols.iter()
+                    .zip(col_ranks.iter())
+                    .flat_map(|(c, cr)| [c[r].to_string(), cr[r].to_string()])
+                    .collect()
+            })
+            .collect();
+        self.names = names;
+        self.matrix = matrix;
+        self.tests = tests;
+        Ok(())
+    }
+}
+
+impl Widget for &mut RankCorr {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        if self.kstrings.is_empty() {
+            self.kstrings = vec![
+                ("x".to_string(), "".to_string()),
+                ("y".to_string(), "".to_string()),
+            ];
+        }
+        let mut resp = ui
+            .horizontal(|ui| {
+                let mut changed = false;
+                for (m, name) in [
+                    (CorrMethod::Pearson, "Pearson"),
+                    (CorrMethod::Spearman, "Spearman"),
+                    (CorrMethod::Kendall, "Kendall"),
+                ] {
+                    changed |= ui.radio_value(&mut self.method, m, name).changed();
+                }
+                changed
+            })
+            .inner;
+        resp |= ui.alt_radio(&mut self.alt);
+        let rba = ui.button("Add column");
+        if rba.clicked() {
+            self.kstrings
+                .push((format!("x{}", self.kstrings.len() + 1), "".to_string()));
+        }
+        let rbr = ui.button("Remove column");
+        if rbr.clicked() && self.kstrings.len() > 2 {
+            self.kstrings.pop();
+        }
+        let mut r = rba.union(rbr);
+        r = r.union(
+            ui.horizontal(|ui| {
+                let mut resp = empty_resp(ui);
+                for (name, d) in self.kstrings.iter_mut() {
+                    resp = resp.union(
+                        ui.vertical(|ui| {
+                            let resp = ui.add_sized((100., 20.), TextEdit::singleline(name));
+                            resp.union(ui.data_box("", d))
+                        })
+                        .inner,
+                    );
+                }
+                resp
+            })
+            .inner,
+        );
+        if resp {
+            r.mark_changed();
+        }
+        if r.changed() {
+            self.tests.clear();
+            self.strings[0] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.label(RichText::new(&self.strings[0]).color(Color32::DARK_RED));
+        if self.tests.is_empty() {
+            return r;
+        }
+        let coef = match self.method {
+            CorrMethod::Pearson => "r",
+            CorrMethod::Spearman => "rho",
+            CorrMethod::Kendall => "tau-b",
+        };
+        ui.table(
+            "corr tests",
+            &["", "", coef, "Statistic", "Method", "p"],
+            &self.tests,
+        );
+
+        let k = self.names.len();
+        let xnames = self.names.clone();
+        let ynames = self.names.clone();
+        Plot::new("Correlation heatmap")
+            .height(250.)
+            .data_aspect(1.0)
+            .x_axis_formatter(move |x, _| {
+                if x.fract() == 0.0 && x >= 0.0 {
+                    xnames.get(x as usize).cloned().unwrap_or_default()
+                } else {
+                    "".to_string()
+                }
+            })
+            .y_axis_formatter(move |y, _| {
+                if y.fract() == 0.0 && y >= 0.0 && (y as usize) < k {
+                    ynames[k - 1 - y as usize].clone()
+                } else {
+                    "".to_string()
+                }
+            })
+            .show(ui, |ui| {
+                for (i, row) in self.matrix.iter().enumerate() {
+                    for (j, r) in row.iter().enumerate() {
+                        let (x, y) = (j as f64, (k - 1 - i) as f64);
+                        let color = if *r >= 0.0 {
+                            Color32::BLUE
+                        } else {
+                            Color32::RED
+                        };
+                        ui.polygon(
+                            Polygon::new(vec![
+                                [x - 0.5, y - 0.5],
+                                [x + 0.5, y - 0.5],
+                                [x + 0.5, y + 0.5],
+                                [x - 0.5, y + 0.5],
+                            ])
+                            .color(color)
+                            .fill_alpha(r.abs() as f32),
+                        );
+                        ui.text(Text::new(PlotPoint::new(x, y), fmt_num(*r)));
+                    }
+                }
+            });
+
+        let header: Vec<&str> = self.rank_header.iter().map(|h| h.as_str()).collect();
+        ui.table("corr ranks", &header, &self.ranks);
+        r
     }
 }
@@ -1,14 +1,20 @@
-use std::fs::File;
+use std::{fmt::Display, fs::File};
 
 use egui::{
-    plot::{Line, Polygon},
-    Color32, RichText, Ui, Widget,
+    plot::{Legend, Line, Plot, Polygon, VLine},
+    Color32, RichText, Slider, Ui, Widget,
 };
 use meval::Expr;
 use opencrunch_derive::crunch_fill_eval;
-use statrs::distribution::{Continuous, ContinuousCDF};
+use statrs::{
+    distribution::{Continuous, ContinuousCDF},
+    function::gamma::{digamma, ln_gamma},
+    statistics::{Distribution as _, Mode},
+};
 
-use crate::{empty_resp, Constr, NumBox};
+use crate::{
+    calcs::AltRadio, empty_resp, fmt_num, hyptest::Alternative, Constr, NumBox, TableView,
+};
 
 trait TryContinuous {
     fn pdf(&self, x: f64) -> Option<f64>;
@@ -20,6 +26,60 @@ trait Fillable {
     fn fill(&mut self) -> Result<(), &str>;
 }
 
+/// Rows of the properties panel, None until the parameters are valid.
+trait Properties {
+    fn properties(&self) -> Option<Vec<Vec<String>>>;
+}
+
+/// Quantiles listed in the properties panel.
+const QUANTILES: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+/// Properties panel rows from the moments, with missing ones shown as undefined.
+fn property_rows(
+    mean: Option<f64>,
+    variance: Option<f64>,
+    mode: Option<f64>,
+    skewness: Option<f64>,
+    kurtosis: Option<f64>,
+    entropy: Option<f64>,
+    quantile: impl Fn(f64) -> Option<f64>,
+) -> Vec<Vec<String>> {
+    let show = |x: Option<f64>| {
+        x.filter(|x| !x.is_nan())
+            .map_or("undefined".to_string(), fmt_num)
+    };
+    let mut rows = vec![
+        vec!["mean".to_string(), show(mean)],
+        vec!["variance".to_string(), show(variance)],
+        vec!["sd".to_string(), show(variance.map(f64::sqrt))],
+        vec!["median".to_string(), show(quantile(0.5))],
+        vec!["mode".to_string(), show(mode)],
+        vec!["skewness".to_string(), show(skewness)],
+        vec!["excess kurtosis".to_string(), show(kurtosis)],
+        vec!["entropy".to_string(), show(entropy)],
+    ];
+    for p in QUANTILES {
+        rows.push(vec![format!("{}%", (p * 100.0).round()), show(quantile(p))]);
+    }
+    rows
+}
+
+/// Properties of a statrs distribution, which has no excess kurtosis to give.
+fn dist_properties<D>(d: &D, mode: Option<f64>, kurtosis: Option<f64>) -> Vec<Vec<String>>
+where
+    D: statrs::statistics::Distribution<f64> + ContinuousCDF<f64, f64>,
+{
+    property_rows(
+        d.mean(),
+        d.variance(),
+        mode,
+        d.skewness(),
+        kurtosis,
+        d.entropy(),
+        |p| Some(d.inverse_cdf(p)),
+    )
+}
+
 trait Graph: TryContinuous {
     fn get_height(&self, pos: f64) -> Option<f64>;
     fn start(&self) -> f64;
@@ -39,7 +99,7 @@ trait Graph: TryContinuous {
         let gap = self.get_gap();
         (0..self.get_terms())
             .map(|x| s + (x as f64) * gap)
-            .map(|x| (x, self.pdf(x)))
+            .map(|x| (x, self.get_height(x)))
             .filter(|(_, v)| v.is_some())
             .map(|(x, v)| [x, v.unwrap()])
             .collect()
@@ -136,20 +196,45 @@ fn find_zero(f: impl Fn(f64) -> Option<f64>) -> Option<f64> {
 #[derive(Clone)]
 pub(crate) struct OpenCrunchCDistr {
     distr: CDistr,
+    errors: Option<ErrorViz>,
     graph: Vec<[f64; 2]>,
     fill: Vec<[[f64; 2]; 4]>,
+    properties: Option<Vec<Vec<String>>>,
 }
 
 impl Default for OpenCrunchCDistr {
     fn default() -> Self {
         Self {
             distr: CDistr::None,
+            errors: None,
             graph: vec![],
             fill: vec![],
+            properties: None,
         }
     }
 }
 
+impl OpenCrunchCDistr {
+    /// Switches to the normal family with the given parameters.
+    pub(crate) fn show_normal(&mut self, normal: statrs::distribution::Normal) {
+        use statrs::statistics::Distribution as _;
+        let (mean, sd) = (
+            normal.mean().expect("Normal has a mean"),
+            normal.std_dev().expect("Normal has an sd"),
+        );
+        let mut n = Normal::default();
+        n.strings[0] = mean.to_string();
+        n.strings[1] = sd.to_string();
+        n.strings[2] = format!("<{mean}");
+        n.mean = Constr::EQ(mean);
+        n.sd = Constr::EQ(sd);
+        n.xval = Constr::LT(mean);
+        self.distr = CDistr::Normal(n);
+        self.errors = None;
+        self.graph = vec![];
+    }
+}
+
 impl Widget for &mut OpenCrunchCDistr {
     fn ui(self, ui: &mut Ui) -> egui::Response {
         let ctx = ui.ctx();
@@ -158,24 +243,38 @@ impl Widget for &mut OpenCrunchCDistr {
             ui.horizontal(|ui| {
                 if ui.button("Normal").clicked() {
                     self.distr = CDistr::Normal(Normal::default());
+                    self.errors = None;
                     self.graph = vec![];
                 }
                 if ui.button("Chi Squared").clicked() {
                     self.distr = CDistr::ChiSquare(ChiSquare::default());
+                    self.errors = None;
                     self.graph = vec![];
                 }
                 if ui.button("T Distribution").clicked() {
                     self.distr = CDistr::TDist(TDist::default());
+                    self.errors = None;
                     self.graph = vec![];
                 }
                 if ui.button("F Distribution").clicked() {
                     self.distr = CDistr::FDist(FDist::default());
+                    self.errors = None;
                     self.graph = vec![];
                 }
                 if ui.button("Exponential").clicked() {
                     self.distr = CDistr::Exp(Expon::default());
+                    self.errors = None;
+                    self.graph = vec![];
+                }
+                if ui.button("Custom").clicked() {
+                    self.distr = CDistr::Custom(Custom::default());
+                    self.errors = None;
                     self.graph = vec![];
                 }
+                if ui.button("Type I/II Errors").clicked() {
+                    self.distr = CDistr::None;
+                    self.errors = Some(ErrorViz::default());
+                }
             });
         });
 
@@ -186,6 +285,13 @@ impl Widget for &mut OpenCrunchCDistr {
             eprintln!("{}: {}", name, len);
         }
 
+        if let Some(errors) = &mut self.errors {
+            egui::panel::TopBottomPanel::bottom("Interactive").show(ctx, |ui| ui.add(&mut *errors));
+            return egui::panel::CentralPanel::default()
+                .show(ctx, |ui| errors.plot(ui))
+                .response;
+        }
+
         let resp = egui::panel::TopBottomPanel::bottom("Interactive")
             .show(ctx, |ui| ui.add(&mut self.distr))
             .inner;
@@ -193,6 +299,13 @@ impl Widget for &mut OpenCrunchCDistr {
         if (self.graph.is_empty() || resp.changed()) && !self.distr.is_none() {
             self.graph = self.distr.get_line();
             self.fill = self.distr.get_fill();
+            self.properties = self.distr.properties();
+        }
+        if let Some(rows) = &self.properties {
+            egui::panel::SidePanel::right("Properties").show(ctx, |ui| {
+                ui.heading("Properties");
+                ui.table("properties", &["", "value"], rows);
+            });
         }
         let line = Line::new(self.graph.clone());
         let polys: Vec<_> = self
@@ -213,16 +326,21 @@ impl Widget for &mut OpenCrunchCDistr {
     }
 }
 
-impl ToString for OpenCrunchCDistr {
-    fn to_string(&self) -> String {
-        match self.distr {
-            CDistr::None => "OpenCrunch - Distributions".to_owned(),
-            CDistr::Normal(_) => "OpenCrunch - Distributions - Normal".to_owned(),
-            CDistr::ChiSquare(_) => "OpenCrunch - Distributions - Chi Square".to_owned(),
-            CDistr::TDist(_) => "OpenCrunch - Distributions - T".to_owned(),
-            CDistr::FDist(_) => "OpenCrunch - Distributions - F".to_owned(),
-            CDistr::Exp(_) => "OpenCrunch - Distributions - Exponential".to_owned(),
+impl Display for OpenCrunchCDistr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.errors.is_some() {
+            return f.write_str("OpenCrunch - Distributions - Type I/II Errors");
         }
+        let name = match self.distr {
+            CDistr::None => "OpenCrunch - Distributions",
+            CDistr::Normal(_) => "OpenCrunch - Distributions - Normal",
+            CDistr::ChiSquare(_) => "OpenCrunch - Distributions - Chi Square",
+            CDistr::TDist(_) => "OpenCrunch - Distributions - T",
+            CDistr::FDist(_) => "OpenCrunch - Distributions - F",
+            CDistr::Exp(_) => "OpenCrunch - Distributions - Exponential",
+            CDistr::Custom(_) => "OpenCrunch - Distributions - Custom",
+        };
+        f.write_str(name)
     }
 }
 
@@ -234,6 +352,7 @@ enum CDistr {
     TDist(TDist),
     FDist(FDist),
     Exp(Expon),
+    Custom(Custom),
 }
 
 impl TryContinuous for CDistr {
@@ -244,6 +363,7 @@ impl TryContinuous for CDistr {
             CDistr::ChiSquare(c) => c.pdf(x),
             CDistr::TDist(t) => t.pdf(x),
             CDistr::Exp(e) => e.pdf(x),
+            CDistr::Custom(c) => c.pdf(x),
             CDistr::FDist(f) => f.pdf(x),
         }
     }
@@ -255,6 +375,7 @@ impl TryContinuous for CDistr {
             CDistr::ChiSquare(c) => c.cdf(x),
             CDistr::TDist(t) => t.cdf(x),
             CDistr::Exp(e) => e.cdf(x),
+            CDistr::Custom(c) => c.cdf(x),
             CDistr::FDist(f) => f.cdf(x),
         }
     }
@@ -266,6 +387,7 @@ impl TryContinuous for CDistr {
             CDistr::ChiSquare(c) => c.inverse_cdf(x),
             CDistr::TDist(t) => t.inverse_cdf(x),
             CDistr::Exp(e) => e.inverse_cdf(x),
+            CDistr::Custom(c) => c.inverse_cdf(x),
             CDistr::FDist(f) => f.inverse_cdf(x),
         }
     }
@@ -279,6 +401,7 @@ impl Graph for CDistr {
             CDistr::ChiSquare(c) => c.pdf(pos),
             CDistr::TDist(t) => t.pdf(pos),
             CDistr::Exp(e) => e.pdf(pos),
+            CDistr::Custom(c) => c.pdf(pos),
             CDistr::FDist(f) => f.pdf(pos),
         }
     }
@@ -290,6 +413,7 @@ impl Graph for CDistr {
             CDistr::ChiSquare(c) => c.start(),
             CDistr::TDist(t) => t.start(),
             CDistr::Exp(e) => e.start(),
+            CDistr::Custom(c) => c.start(),
             CDistr::FDist(f) => f.start(),
         }
     }
@@ -301,6 +425,7 @@ impl Graph for CDistr {
             CDistr::ChiSquare(c) => c.end(),
             CDistr::TDist(t) => t.end(),
             CDistr::Exp(e) => e.end(),
+            CDistr::Custom(c) => c.end(),
             CDistr::FDist(f) => f.end(),
         }
     }
@@ -312,6 +437,7 @@ impl Graph for CDistr {
             CDistr::ChiSquare(c) => c.is_selected(pos),
             CDistr::TDist(t) => t.is_selected(pos),
             CDistr::Exp(e) => e.is_selected(pos),
+            CDistr::Custom(c) => c.is_selected(pos),
             CDistr::FDist(f) => f.is_selected(pos),
         }
     }
@@ -323,6 +449,20 @@ impl CDistr {
     }
 }
 
+impl Properties for CDistr {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        match self {
+            CDistr::None => None,
+            CDistr::Normal(n) => n.properties(),
+            CDistr::ChiSquare(c) => c.properties(),
+            CDistr::TDist(t) => t.properties(),
+            CDistr::FDist(f) => f.properties(),
+            CDistr::Exp(e) => e.properties(),
+            CDistr::Custom(c) => c.properties(),
+        }
+    }
+}
+
 impl Widget for &mut CDistr {
     fn ui(self, ui: &mut Ui) -> egui::Response {
         match self {
@@ -331,11 +471,180 @@ impl Widget for &mut CDistr {
             CDistr::ChiSquare(c) => c.ui(ui),
             CDistr::TDist(t) => t.ui(ui),
             CDistr::Exp(e) => e.ui(ui),
+            CDistr::Custom(c) => c.ui(ui),
             CDistr::FDist(f) => f.ui(ui),
         }
     }
 }
 
+/// A normal curve over a shared range, selected inside or outside [lo, hi].
+struct Region {
+    dist: statrs::distribution::Normal,
+    start: f64,
+    end: f64,
+    lo: f64,
+    hi: f64,
+    inside: bool,
+}
+
+impl TryContinuous for Region {
+    fn pdf(&self, x: f64) -> Option<f64> {
+        Some(self.dist.pdf(x))
+    }
+
+    fn cdf(&self, x: f64) -> Option<f64> {
+        Some(self.dist.cdf(x))
+    }
+
+    fn inverse_cdf(&self, x: f64) -> Option<f64> {
+        Some(self.dist.inverse_cdf(x))
+    }
+}
+
+impl Graph for Region {
+    fn get_height(&self, pos: f64) -> Option<f64> {
+        self.pdf(pos)
+    }
+
+    fn start(&self) -> f64 {
+        self.start
+    }
+
+    fn end(&self) -> f64 {
+        self.end
+    }
+
+    fn is_selected(&self, pos: f64) -> bool {
+        (self.lo..=self.hi).contains(&pos) == self.inside
+    }
+}
+
+/// Null and alternative sampling distributions of a z test on a mean,
+/// in units of the population standard deviation.
+#[derive(Debug, Clone)]
+struct ErrorViz {
+    n: u32,
+    effect: f64,
+    alpha: f64,
+    alt: Alternative,
+}
+
+impl Default for ErrorViz {
+    fn default() -> Self {
+        Self {
+            n: 25,
+            effect: 0.5,
+            alpha: 0.05,
+            alt: Alternative::TwoSided,
+        }
+    }
+}
+
+impl ErrorViz {
+    /// Null and alternative curves, shaded where H0 is rejected and where it isn't.
+    fn regions(&self) -> (Region, Region) {
+        let se = 1.0 / (self.n as f64).sqrt();
+        let snd = statrs::distribution::Normal::new(0.0, 1.0).expect("SND cant fail");
+        let (lo, hi) = match self.alt {
+            Alternative::TwoSided => {
+                let c = snd.inverse_cdf(1.0 - self.alpha / 2.0) * se;
+                (-c, c)
+            }
+            Alternative::Less => (snd.inverse_cdf(self.alpha) * se, f64::INFINITY),
+            Alternative::Greater => (f64::NEG_INFINITY, snd.inverse_cdf(1.0 - self.alpha) * se),
+        };
+        let start = self.effect.min(0.0) - 4.0 * se;
+        let end = self.effect.max(0.0) + 4.0 * se;
+        let region = |mean, inside| Region {
+            dist: statrs::distribution::Normal::new(mean, se).expect("se is positive"),
+            start,
+            end,
+            lo,
+            hi,
+            inside,
+        };
+        (region(0.0, false), region(self.effect, true))
+    }
+
+    fn plot(&self, ui: &mut Ui) -> egui::Response {
+        let (null, alt) = self.regions();
+        let strips = |r: &Region, color: Color32, name: &str| {
+            r.get_fill()
+                .into_iter()
+                .map(|x| {
+                    Polygon::new(x.to_vec())
+                        .color(color)
+                        .fill_alpha(0.6)
+                        .name(name)
+                })
+                .collect::<Vec<_>>()
+        };
+        let alpha = strips(&null, Color32::RED, "α");
+        let beta = strips(&alt, Color32::BLUE, "β");
+        Plot::new("Errors")
+            .legend(Legend::default())
+            .show(ui, |ui| {
+                ui.line(Line::new(null.get_line()).color(Color32::BLACK).name("H0"));
+                ui.line(
+                    Line::new(alt.get_line())
+                        .color(Color32::DARK_GREEN)
+                        .name("H1"),
+                );
+                for p in alpha.into_iter().chain(beta) {
+                    ui.polygon(p);
+                }
+                for c in [null.lo, null.hi].into_iter().filter(|c| c.is_finite()) {
+                    ui.vline(VLine::new(c).color(Color32::GRAY));
+                }
+            })
+            .response
+    }
+}
+
+impl Widget for &mut ErrorViz {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.add(Slider::new(&mut self.n, 1..=500).text("n"));
+        resp = resp.union(ui.add(Slider::new(&mut self.effect, -3.0..=3.0).text("Effect size d")));
+        resp = resp.union(
+            ui.add(
+                Slider::new(&mut self.alpha, 0.001..=0.5)
+                    .logarithmic(true)
+                    .text("α"),
+            ),
+        );
+        if ui.alt_radio(&mut self.alt) {
+            resp.mark_changed();
+        }
+        let (_, alt) = self.regions();
+        let beta = alt.cdf(alt.hi).unwrap_or(1.0) - alt.cdf(alt.lo).unwrap_or(0.0);
+        let crit = [alt.lo, alt.hi]
+            .into_iter()
+            .filter(|c| c.is_finite())
+            .map(|c| format!("{c:.4}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        ui.label(format!(
+            "Reject H0 outside [{}, {}], critical value(s) {crit}",
+            if alt.lo.is_finite() {
+                format!("{:.4}", alt.lo)
+            } else {
+                "-∞".to_string()
+            },
+            if alt.hi.is_finite() {
+                format!("{:.4}", alt.hi)
+            } else {
+                "∞".to_string()
+            },
+        ));
+        ui.label(format!(
+            "α = {:.4}    β = {beta:.4}    Power = {:.4}",
+            self.alpha,
+            1.0 - beta
+        ));
+        resp
+    }
+}
+
 #[crunch_fill_eval]
 #[derive(Debug, Clone)]
 struct Normal {
@@ -363,29 +672,30 @@ impl Default for Normal {
     }
 }
 
+impl Normal {
+    fn dist(&self) -> Option<statrs::distribution::Normal> {
+        statrs::distribution::Normal::new(*self.mean.as_val()?, *self.sd.as_val()?).ok()
+    }
+}
+
+impl Properties for Normal {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        let d = self.dist()?;
+        Some(dist_properties(&d, d.mode(), Some(0.0)))
+    }
+}
+
 impl TryContinuous for Normal {
     fn pdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::Normal::new(*self.mean.as_val()?, *self.sd.as_val()?)
-                .ok()?
-                .pdf(x),
-        )
+        Some(self.dist()?.pdf(x))
     }
 
     fn cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::Normal::new(*self.mean.as_val()?, *self.sd.as_val()?)
-                .ok()?
-                .cdf(x),
-        )
+        Some(self.dist()?.cdf(x))
     }
 
     fn inverse_cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::Normal::new(*self.mean.as_val()?, *self.sd.as_val()?)
-                .ok()?
-                .inverse_cdf(x),
-        )
+        Some(self.dist()?.inverse_cdf(x))
     }
 }
 
@@ -570,29 +880,34 @@ impl Default for ChiSquare {
     }
 }
 
+impl ChiSquare {
+    fn dist(&self) -> Option<statrs::distribution::ChiSquared> {
+        statrs::distribution::ChiSquared::new(*self.freedom.as_val()?).ok()
+    }
+}
+
+impl Properties for ChiSquare {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        let d = self.dist()?;
+        Some(dist_properties(
+            &d,
+            d.mode().map(|m| m.max(0.0)),
+            Some(12.0 / self.freedom.as_val()?),
+        ))
+    }
+}
+
 impl TryContinuous for ChiSquare {
     fn pdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::ChiSquared::new(*self.freedom.as_val()?)
-                .ok()?
-                .pdf(x),
-        )
+        Some(self.dist()?.pdf(x))
     }
 
     fn cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::ChiSquared::new(*self.freedom.as_val()?)
-                .ok()?
-                .cdf(x),
-        )
+        Some(self.dist()?.cdf(x))
     }
 
     fn inverse_cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::ChiSquared::new(*self.freedom.as_val()?)
-                .ok()?
-                .inverse_cdf(x),
-        )
+        Some(self.dist()?.inverse_cdf(x))
     }
 }
 
@@ -752,41 +1067,43 @@ impl Default for TDist {
     }
 }
 
+impl TDist {
+    fn dist(&self) -> Option<statrs::distribution::StudentsT> {
+        statrs::distribution::StudentsT::new(
+            *self.location.as_val()?,
+            *self.scale.as_val()?,
+            *self.freedom.as_val()?,
+        )
+        .ok()
+    }
+}
+
+impl Properties for TDist {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        let d = self.dist()?;
+        let v = *self.freedom.as_val()?;
+        let kurtosis = if v > 4.0 {
+            Some(6.0 / (v - 4.0))
+        } else if v > 2.0 {
+            Some(f64::INFINITY)
+        } else {
+            None
+        };
+        Some(dist_properties(&d, d.mode(), kurtosis))
+    }
+}
+
 impl TryContinuous for TDist {
     fn pdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::StudentsT::new(
-                *self.location.as_val()?,
-                *self.scale.as_val()?,
-                *self.freedom.as_val()?,
-            )
-            .ok()?
-            .pdf(x),
-        )
+        Some(self.dist()?.pdf(x))
     }
 
     fn cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::StudentsT::new(
-                *self.location.as_val()?,
-                *self.scale.as_val()?,
-                *self.freedom.as_val()?,
-            )
-            .ok()?
-            .cdf(x),
-        )
+        Some(self.dist()?.cdf(x))
     }
 
     fn inverse_cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::StudentsT::new(
-                *self.location.as_val()?,
-                *self.scale.as_val()?,
-                *self.freedom.as_val()?,
-            )
-            .ok()?
-            .inverse_cdf(x),
-        )
+        Some(self.dist()?.inverse_cdf(x))
     }
 }
 
@@ -1056,38 +1373,53 @@ impl Default for FDist {
     }
 }
 
+impl FDist {
+    fn dist(&self) -> Option<statrs::distribution::FisherSnedecor> {
+        statrs::distribution::FisherSnedecor::new(
+            *self.freedom1.as_val()?,
+            *self.freedom2.as_val()?,
+        )
+        .ok()
+    }
+}
+
+impl Properties for FDist {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        let d = self.dist()?;
+        let (d1, d2) = (*self.freedom1.as_val()?, *self.freedom2.as_val()?);
+        let kurtosis = (d2 > 8.0).then(|| {
+            let top = d1 * (5.0 * d2 - 22.0) * (d1 + d2 - 2.0) + (d2 - 4.0) * (d2 - 2.0).powi(2);
+            12.0 * top / (d1 * (d2 - 6.0) * (d2 - 8.0) * (d1 + d2 - 2.0))
+        });
+        // statrs has no entropy for the F, so it comes from the closed form
+        let (a, b) = (d1 / 2.0, d2 / 2.0);
+        let entropy = ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b) + (1.0 - a) * digamma(a)
+            - (1.0 + b) * digamma(b)
+            + (a + b) * digamma(a + b)
+            + (d2 / d1).ln();
+        Some(property_rows(
+            d.mean(),
+            d.variance(),
+            d.mode(),
+            d.skewness(),
+            kurtosis,
+            Some(entropy),
+            |p| Some(d.inverse_cdf(p)),
+        ))
+    }
+}
+
 impl TryContinuous for FDist {
     fn pdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::FisherSnedecor::new(
-                *self.freedom1.as_val()?,
-                *self.freedom2.as_val()?,
-            )
-            .ok()?
-            .pdf(x),
-        )
+        Some(self.dist()?.pdf(x))
     }
 
     fn cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::FisherSnedecor::new(
-                *self.freedom1.as_val()?,
-                *self.freedom2.as_val()?,
-            )
-            .ok()?
-            .cdf(x),
-        )
+        Some(self.dist()?.cdf(x))
     }
 
     fn inverse_cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::FisherSnedecor::new(
-                *self.freedom1.as_val()?,
-                *self.freedom2.as_val()?,
-            )
-            .ok()?
-            .inverse_cdf(x),
-        )
+        Some(self.dist()?.inverse_cdf(x))
     }
 }
 
@@ -1205,7 +1537,6 @@ struct Expon {
 
 impl Default for Expon {
     fn default() -> Self {
-        todo!();
         Self {
             mean: Some(1.0),
             xval: Some(1.0),
@@ -1220,21 +1551,30 @@ impl Default for Expon {
     }
 }
 
+impl Expon {
+    fn dist(&self) -> Option<statrs::distribution::Exp> {
+        statrs::distribution::Exp::new(self.mean?).ok()
+    }
+}
+
+impl Properties for Expon {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        let d = self.dist()?;
+        Some(dist_properties(&d, d.mode(), Some(6.0)))
+    }
+}
+
 impl TryContinuous for Expon {
     fn pdf(&self, x: f64) -> Option<f64> {
-        Some(statrs::distribution::Exp::new(self.mean?).ok()?.pdf(x))
+        Some(self.dist()?.pdf(x))
     }
 
     fn cdf(&self, x: f64) -> Option<f64> {
-        Some(statrs::distribution::Exp::new(self.mean?).ok()?.cdf(x))
+        Some(self.dist()?.cdf(x))
     }
 
     fn inverse_cdf(&self, x: f64) -> Option<f64> {
-        Some(
-            statrs::distribution::Exp::new(self.mean?)
-                .ok()?
-                .inverse_cdf(x),
-        )
+        Some(self.dist()?.inverse_cdf(x))
     }
 }
 
@@ -1317,6 +1657,322 @@ impl Fillable for Expon {
     }
 }
 
+/// Steps for integrating a custom pdf over its support.
+const CUSTOM_STEPS: usize = 4000;
+/// Most a custom pdf may integrate away from 1.
+const CUSTOM_TOL: f64 = 1e-4;
+/// Steps at each end of the support integrated finely, for pdfs that spike there.
+const END_STEPS: usize = 16;
+/// Times the first and last steps are halved toward the ends of the support.
+const END_HALVINGS: usize = 40;
+/// Midpoints in each finely integrated piece.
+const PIECE_STEPS: usize = 8;
+
+/// A distribution from a pdf in x typed by the user, integrated numerically over its support.
+#[derive(Debug, Clone)]
+struct Custom {
+    xval: Constr<f64>,
+    pval: Constr<f64>,
+    /// Left edge and width of each integration step
+    start: f64,
+    step: f64,
+    /// pdf at the middle of each step
+    heights: Vec<f64>,
+    /// Area to the left of each step
+    areas: Vec<f64>,
+    mean: f64,
+    variance: f64,
+    /// pdf, support, x value, prob, mean, variance, error
+    strings: [String; 7],
+}
+
+impl Default for Custom {
+    fn default() -> Self {
+        let mut c = Self {
+            xval: Constr::LT(0.5),
+            pval: Constr::None,
+            start: 0.0,
+            step: 0.0,
+            heights: vec![],
+            areas: vec![],
+            mean: 0.0,
+            variance: 0.0,
+            strings: [
+                "3*x^2".to_string(),
+                "[0,1]".to_string(),
+                "<0.5".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        };
+        c.strings[6] = c.build().err().unwrap_or_default();
+        c
+    }
+}
+
+impl Custom {
+    /// Integrates the pdf over the support, checking it is a valid density.
+    fn build(&mut self) -> Result<(), String> {
+        self.heights.clear();
+        self.areas.clear();
+        self.strings[4].clear();
+        self.strings[5].clear();
+        let f = self.strings[0]
+            .parse::<Expr>()
+            .and_then(|e| e.bind("x"))
+            .map_err(|e| format!("pdf is invalid: {e}"))?;
+        let Ok(Constr::In(a, b)) = self.strings[1].trim().parse::<Constr<f64>>() else {
+            return Err("Support must be an interval like [0,1]".to_string());
+        };
+        if !a.is_finite() || !b.is_finite() || a >= b {
+            return Err("Support must be a finite interval like [0,1]".to_string());
+        }
+        let step = (b - a) / CUSTOM_STEPS as f64;
+        // Midpoints, so a pdf that blows up at an end of the support still integrates
+        let mids: Vec<f64> = (0..CUSTOM_STEPS)
+            .map(|i| a + (i as f64 + 0.5) * step)
+            .collect();
+        let mut heights: Vec<f64> = mids.iter().map(|x| f(*x)).collect();
+        let invalid = "The pdf must be a non negative number across the support";
+        if heights.iter().any(|y| !y.is_finite() || *y < 0.0) {
+            return Err(invalid.to_string());
+        }
+        // Refined steps hold their average height, so the cdf stays linear within each step
+        heights[0] = end_area(&f, a, step).ok_or(invalid)? / step;
+        heights[CUSTOM_STEPS - 1] = end_area(&f, b, -step).ok_or(invalid)? / step;
+        for i in 1..END_STEPS {
+            let right = CUSTOM_STEPS - 1 - i;
+            heights[i] = fine_area(&f, a + i as f64 * step, step).ok_or(invalid)? / step;
+            heights[right] = fine_area(&f, a + right as f64 * step, step).ok_or(invalid)? / step;
+        }
+        let mut areas = Vec::with_capacity(CUSTOM_STEPS);
+        let mut total = 0.0;
+        for h in &heights {
+            areas.push(total);
+            total += h * step;
+        }
+        if (total - 1.0).abs() > CUSTOM_TOL {
+            return Err(format!("The pdf integrates to {}, not 1", fmt_num(total)));
+        }
+        // Rescale away the integration error so the cdf ends at exactly 1
+        let heights: Vec<f64> = heights.iter().map(|h| h / total).collect();
+        let areas: Vec<f64> = areas.iter().map(|a| a / total).collect();
+        let mean: f64 = mids.iter().zip(&heights).map(|(x, y)| x * y * step).sum();
+        let square: f64 = mids
+            .iter()
+            .zip(&heights)
+            .map(|(x, y)| x * x * y * step)
+            .sum();
+        self.start = a;
+        self.step = step;
+        self.heights = heights;
+        self.areas = areas;
+        self.mean = mean;
+        self.variance = square - mean * mean;
+        self.strings[4] = fmt_num(self.mean);
+        self.strings[5] = fmt_num(self.variance);
+        Ok(())
+    }
+
+    /// Index of the step holding x, if x is in the support.
+    fn step_of(&self, x: f64) -> Option<usize> {
+        let i = ((x - self.start) / self.step).floor();
+        (i >= 0.0 && (i as usize) < self.heights.len()).then_some(i as usize)
+    }
+}
+
+/// Area under f over the step of the given width at an edge of the support, the width being
+/// negative at the right edge. The step is cut into pieces that halve toward the edge, so a
+/// pdf that spikes there is still integrated. None if f is negative or not a number.
+fn end_area(f: &impl Fn(f64) -> f64, edge: f64, width: f64) -> Option<f64> {
+    let mut area = 0.0;
+    let mut w = width;
+    for _ in 0..END_HALVINGS {
+        // The piece between edge + w and edge + 2w
+        w /= 2.0;
+        area += fine_area(f, edge + w, w)?;
+    }
+    Some(area)
+}
+
+/// Area under f from lo over the given width, negative to go left, by `PIECE_STEPS` midpoints.
+fn fine_area(f: &impl Fn(f64) -> f64, lo: f64, width: f64) -> Option<f64> {
+    let sub = width / PIECE_STEPS as f64;
+    let mut area = 0.0;
+    for j in 0..PIECE_STEPS {
+        let y = f(lo + (j as f64 + 0.5) * sub);
+        if !y.is_finite() || y < 0.0 {
+            return None;
+        }
+        area += y * sub.abs();
+    }
+    Some(area)
+}
+
+impl Properties for Custom {
+    fn properties(&self) -> Option<Vec<Vec<String>>> {
+        if self.heights.is_empty() {
+            return None;
+        }
+        let mids = (0..self.heights.len()).map(|i| self.start + (i as f64 + 0.5) * self.step);
+        let moment = |f: &dyn Fn(f64) -> f64| -> f64 {
+            mids.clone()
+                .zip(&self.heights)
+                .map(|(x, h)| f(x) * h * self.step)
+                .sum()
+        };
+        let (mean, var) = (self.mean, self.variance);
+        let skewness = moment(&|x| (x - mean).powi(3)) / var.powf(1.5);
+        let kurtosis = moment(&|x| (x - mean).powi(4)) / (var * var) - 3.0;
+        let entropy: f64 = self
+            .heights
+            .iter()
+            .filter(|h| **h > 0.0)
+            .map(|h| -h * h.ln() * self.step)
+            .sum();
+        let (top, _) =
+            self.heights.iter().enumerate().fold(
+                (0, f64::MIN),
+                |a, (i, h)| if *h > a.1 { (i, *h) } else { a },
+            );
+        let mode = self.start + (top as f64 + 0.5) * self.step;
+        Some(property_rows(
+            Some(mean),
+            Some(var),
+            Some(mode),
+            Some(skewness),
+            Some(kurtosis),
+            Some(entropy),
+            |p| self.inverse_cdf(p),
+        ))
+    }
+}
+
+impl TryContinuous for Custom {
+    fn pdf(&self, x: f64) -> Option<f64> {
+        if self.heights.is_empty() {
+            return None;
+        }
+        Some(self.step_of(x).map(|i| self.heights[i]).unwrap_or(0.0))
+    }
+
+    fn cdf(&self, x: f64) -> Option<f64> {
+        if self.heights.is_empty() {
+            return None;
+        }
+        Some(match self.step_of(x) {
+            Some(i) => self.areas[i] + self.heights[i] * (x - self.start - i as f64 * self.step),
+            None if x < self.start => 0.0,
+            None => 1.0,
+        })
+    }
+
+    fn inverse_cdf(&self, x: f64) -> Option<f64> {
+        if self.heights.is_empty() || !(0.0..=1.0).contains(&x) {
+            return None;
+        }
+        // The last step starting at or below x, then along it as the cdf is linear there
+        let i = self.areas.partition_point(|a| *a <= x).max(1) - 1;
+        let along = if self.heights[i] > 0.0 {
+            ((x - self.areas[i]) / self.heights[i]).min(self.step)
+        } else {
+            0.0
+        };
+        Some(self.start + i as f64 * self.step + along)
+    }
+}
+
+impl Graph for Custom {
+    fn get_height(&self, pos: f64) -> Option<f64> {
+        self.pdf(pos)
+    }
+
+    fn start(&self) -> f64 {
+        self.start
+    }
+
+    fn end(&self) -> f64 {
+        self.start + self.step * self.heights.len() as f64
+    }
+
+    fn is_selected(&self, pos: f64) -> bool {
+        self.xval.comp(&pos)
+    }
+}
+
+impl Widget for &mut Custom {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut pdf = ui.num_box("pdf f(x)", &mut self.strings[0]);
+        pdf = pdf.union(ui.num_box("support", &mut self.strings[1]));
+        let mut resp = pdf.clone();
+        resp = resp.union(ui.num_box("x value", &mut self.strings[2]));
+        resp = resp.union(ui.num_box("prob", &mut self.strings[3]));
+        if pdf.changed() {
+            self.strings[6] = self.build().err().unwrap_or_default();
+        }
+        if resp.changed() {
+            self.xval = self.strings[2].parse().unwrap_or(Constr::None);
+            self.pval = self.strings[3].parse().unwrap_or(Constr::None);
+        }
+        ui.num_box("mean", &mut self.strings[4].clone());
+        ui.num_box("variance", &mut self.strings[5].clone());
+        if ui.button("Calculate").clicked() {
+            resp.mark_changed();
+            if let Err(s) = self.fill() {
+                self.strings[6] = s.to_owned();
+            } else {
+                self.strings[6] = "".to_owned();
+            }
+        }
+        resp = resp
+            .union(ui.label(RichText::new(&self.strings[6]).background_color(Color32::DARK_RED)));
+        resp
+    }
+}
+
+impl Fillable for Custom {
+    fn fill(&mut self) -> Result<(), &str> {
+        if self.heights.is_empty() {
+            return Err("Enter a valid pdf and support first");
+        }
+        if self.xval.is_some() {
+            let cdf = |x| self.cdf(x).expect("The pdf is valid");
+            let fill = match self.xval {
+                Constr::GE(x) | Constr::GT(x) => 1.0 - cdf(x),
+                Constr::LE(x) | Constr::LT(x) => cdf(x),
+                Constr::In(a, b) => cdf(b) - cdf(a),
+                Constr::Out(a, b) => 1.0 - cdf(b) + cdf(a),
+                _ => return Err("Cannot use exact in a continuous distribution."),
+            };
+            self.pval = Constr::EQ(fill);
+            self.strings[3] = fill.to_string();
+        } else {
+            let Constr::EQ(p) = self.pval else {
+                return Err("Probability must be set");
+            };
+            let fill = match self.xval {
+                Constr::GENone | Constr::GTNone => match self.inverse_cdf(1.0 - p) {
+                    Some(n) => Constr::GE(n),
+                    None => return Err("Not a valid probability."),
+                },
+                Constr::LENone | Constr::LTNone => match self.inverse_cdf(p) {
+                    Some(n) => Constr::LE(n),
+                    None => return Err("Not a valid probability."),
+                },
+                eq if eq.is_eq() => {
+                    return Err("Cannot use exact in a continuous distribution.");
+                }
+                _ => return Err("Cannot use ranges for solving for x values."),
+            };
+            self.xval = fill;
+            self.strings[2] = fill.to_string();
+        }
+        Ok(())
+    }
+}
+
 //Ignore all of this, I'll generalize later
 /*
 enum ConstrErr {
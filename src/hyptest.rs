@@ -0,0 +1,263 @@
+use statrs::distribution::{ChiSquared, ContinuousCDF, FisherSnedecor, Normal, StudentsT};
+
+use crate::{fmt_num, Constr};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub(crate) enum Alternative {
+    #[default]
+    TwoSided,
+    Less,
+    Greater,
+}
+
+impl Alternative {
+    /// Picks the p value from the lower tail P(T <= t) and upper tail P(T >= t).
+    pub(crate) fn pval(&self, lower: f64, upper: f64) -> f64 {
+        match self {
+            Alternative::TwoSided => (2.0 * lower.min(upper)).min(1.0),
+            Alternative::Less => lower,
+            Alternative::Greater => upper,
+        }
+    }
+
+    /// Reads the alternative and hypothesized value from a H1 like `!=0`.
+    pub(crate) fn from_constr(h1: &Constr<f32>) -> Option<(Self, f64)> {
+        match h1 {
+            Constr::GE(v) | Constr::GT(v) => Some((Alternative::Greater, *v as f64)),
+            Constr::LE(v) | Constr::LT(v) => Some((Alternative::Less, *v as f64)),
+            Constr::NE(v) => Some((Alternative::TwoSided, *v as f64)),
+            _ => None,
+        }
+    }
+}
+
+/// Distribution of a test statistic when H0 is true.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum NullDist {
+    Normal,
+    T(f64),
+    ChiSquared(f64),
+    F(f64, f64),
+}
+
+impl NullDist {
+    fn cdf(&self, x: f64) -> Result<f64, String> {
+        if x.is_nan() {
+            return Err("The test statistic is undefined for these values".to_string());
+        }
+        Ok(match self {
+            NullDist::Normal => Normal::new(0.0, 1.0).map_err(|e| e.to_string())?.cdf(x),
+            NullDist::T(df) => StudentsT::new(0.0, 1.0, *df)
+                .map_err(|e| e.to_string())?
+                .cdf(x),
+            NullDist::ChiSquared(df) => ChiSquared::new(*df).map_err(|e| e.to_string())?.cdf(x),
+            NullDist::F(d1, d2) => FisherSnedecor::new(*d1, *d2)
+                .map_err(|e| e.to_string())?
+                .cdf(x),
+        })
+    }
+
+    pub(crate) fn inverse_cdf(&self, p: f64) -> Result<f64, String> {
+        // statrs panics outside (0, 1), or bisects forever for the chi square and F
+        if !(p > 0.0 && p < 1.0) {
+            return Err("Probabilities must be between 0 and 1".to_string());
+        }
+        Ok(match self {
+            NullDist::Normal => Normal::new(0.0, 1.0)
+                .map_err(|e| e.to_string())?
+                .inverse_cdf(p),
+            NullDist::T(df) => StudentsT::new(0.0, 1.0, *df)
+                .map_err(|e| e.to_string())?
+                .inverse_cdf(p),
+            NullDist::ChiSquared(df) => ChiSquared::new(*df)
+                .map_err(|e| e.to_string())?
+                .inverse_cdf(p),
+            NullDist::F(d1, d2) => FisherSnedecor::new(*d1, *d2)
+                .map_err(|e| e.to_string())?
+                .inverse_cdf(p),
+        })
+    }
+
+    /// Degrees of freedom for display, empty for the normal.
+    pub(crate) fn df(&self) -> String {
+        match self {
+            NullDist::Normal => "".to_string(),
+            NullDist::T(df) | NullDist::ChiSquared(df) => df.to_string(),
+            NullDist::F(d1, d2) => format!("{d1}, {d2}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct TestResult {
+    pub(crate) stat: f64,
+    pub(crate) null: NullDist,
+    pub(crate) alternative: Alternative,
+    pub(crate) pval: f64,
+    pub(crate) ci: Constr<f32>,
+    /// Values of the statistic that reject H0 at the chosen alpha
+    pub(crate) region: Constr<f32>,
+}
+
+/// A test whose statistic gets larger as the tested parameter does.
+pub(crate) trait HypothesisTest {
+    /// The statistic when the parameter is h0, and its distribution under H0.
+    fn statistic(&self, h0: f64) -> Result<(f64, NullDist), String>;
+
+    /// Confidence interval for the tested parameter.
+    fn interval(&self, confidence: f64) -> Result<Constr<f32>, String>;
+
+    /// Point estimate of the tested parameter.
+    fn estimate(&self) -> Result<f64, String>;
+
+    /// A lower bound when `kind` is `>`, an upper bound when `<`, otherwise a two sided interval.
+    fn bounds(&self, kind: &Constr<f32>, confidence: f64) -> Result<Constr<f32>, String> {
+        if !kind.is_ineq() || kind.is_range() {
+            return self.interval(confidence);
+        }
+        if confidence <= 0.5 {
+            return Err("One sided bounds need a confidence above 0.5".to_string());
+        }
+        let Constr::In(l, u) = self.interval(2.0 * confidence - 1.0)? else {
+            return Err("No interval for this test".to_string());
+        };
+        Ok(match kind {
+            Constr::GE(_) | Constr::GT(_) | Constr::GENone | Constr::GTNone => Constr::GE(l),
+            _ => Constr::LE(u),
+        })
+    }
+
+    /// How far an interval reaches from the estimate, half its width when two sided.
+    fn reach(&self, ci: &Constr<f32>) -> Result<f64, String> {
+        Ok(match ci {
+            Constr::In(l, u) => (u - l) as f64 / 2.0,
+            Constr::GE(l) | Constr::GT(l) => self.estimate()? - *l as f64,
+            Constr::LE(u) | Constr::LT(u) => *u as f64 - self.estimate()?,
+            _ => return Err("Enter the desired interval or a margin of error".to_string()),
+        })
+    }
+
+    /// Runs the test for a H1 like `>0`.
+    fn run(&self, h1: &Constr<f32>, alpha: f64, confidence: f64) -> Result<TestResult, String> {
+        let (alternative, h0) =
+            Alternative::from_constr(h1).ok_or("Not valid hypothesis".to_string())?;
+        self.test(alternative, h0, alpha, confidence)
+    }
+
+    fn test(
+        &self,
+        alternative: Alternative,
+        h0: f64,
+        alpha: f64,
+        confidence: f64,
+    ) -> Result<TestResult, String> {
+        if alpha <= 0.0 || alpha >= 1.0 {
+            return Err("Alpha must be between 0 and 1".to_string());
+        }
+        if confidence <= 0.0 || confidence >= 1.0 {
+            return Err("Confidence must be between 0 and 1".to_string());
+        }
+        let ci = self.interval(confidence)?;
+        let (stat, null) = self.statistic(h0)?;
+        let lower = null.cdf(stat)?;
+        let pval = alternative.pval(lower, 1.0 - lower);
+        let inv = |p| null.inverse_cdf(p).map(|x| x as f32);
+        let region = match alternative {
+            Alternative::Greater => Constr::GT(inv(1.0 - alpha)?),
+            Alternative::Less => Constr::LT(inv(alpha)?),
+            Alternative::TwoSided => Constr::Out(inv(alpha / 2.0)?, inv(1.0 - alpha / 2.0)?),
+        };
+        Ok(TestResult {
+            stat,
+            null,
+            alternative,
+            pval,
+            ci,
+            region,
+        })
+    }
+}
+
+impl TestResult {
+    /// Plain language decision at level alpha.
+    pub(crate) fn conclusion(&self, alpha: f64) -> String {
+        let tails = match self.alternative {
+            Alternative::TwoSided => "two tailed",
+            Alternative::Less => "lower tailed",
+            Alternative::Greater => "upper tailed",
+        };
+        if self.pval <= alpha {
+            format!(
+                "p = {} ≤ α = {alpha} in this {tails} test, so reject H0: the data support H1.",
+                fmt_num(self.pval)
+            )
+        } else {
+            format!(
+                "p = {} > α = {alpha} in this {tails} test, so fail to reject H0: there is not enough evidence for H1.",
+                fmt_num(self.pval)
+            )
+        }
+    }
+}
+
+/// Confidence level whose bounds of this kind reach `margin` from the estimate.
+pub(crate) fn solve_confidence<T: HypothesisTest>(
+    t: &T,
+    kind: &Constr<f32>,
+    margin: f64,
+) -> Result<f64, String> {
+    let one_sided = kind.is_ineq() && !kind.is_range();
+    let mut lo = if one_sided { 0.5 } else { 0.0 } + 1e-9;
+    let mut hi = 1.0 - 1e-9;
+    let reach = |c| t.bounds(kind, c).and_then(|ci| t.reach(&ci));
+    if margin <= reach(lo)? || margin >= reach(hi)? {
+        return Err("No confidence level gives that margin".to_string());
+    }
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if reach(mid)? < margin {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok((lo + hi) / 2.0)
+}
+
+/// Smallest sample size whose bounds of this kind reach no further than `margin`.
+pub(crate) fn solve_n<T: HypothesisTest + Clone>(
+    t: &T,
+    set_n: impl Fn(&mut T, usize),
+    kind: &Constr<f32>,
+    confidence: f64,
+    margin: f64,
+) -> Result<usize, String> {
+    if margin <= 0.0 {
+        return Err("Margin must be positive".to_string());
+    }
+    let reach = |n| {
+        let mut t = t.clone();
+        set_n(&mut t, n);
+        t.bounds(kind, confidence).and_then(|ci| t.reach(&ci))
+    };
+    let mut hi = 2;
+    while reach(hi)? > margin {
+        hi *= 2;
+        if hi > 1 << 30 {
+            return Err("Margin can't be reached".to_string());
+        }
+    }
+    let mut lo = (hi / 2).max(2);
+    if reach(lo)? <= margin {
+        return Ok(lo);
+    }
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if reach(mid)? > margin {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(hi)
+}
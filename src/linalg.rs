@@ -0,0 +1,94 @@
+/// Result of a least squares fit.
+#[derive(Clone, Debug)]
+pub(crate) struct Fit {
+    pub(crate) coefs: Vec<f64>,
+    pub(crate) fitted: Vec<f64>,
+    pub(crate) resid: Vec<f64>,
+    pub(crate) rss: f64,
+    /// (X'X)^-1, scaled by the residual variance this gives the coefficient covariances.
+    pub(crate) xtx_inv: Vec<Vec<f64>>,
+}
+
+/// Fits y against the columns of x with a Householder QR.
+pub(crate) fn lstsq(cols: &[Vec<f64>], y: &[f64]) -> Result<Fit, &'static str> {
+    let n = y.len();
+    let p = cols.len();
+    if p == 0 {
+        return Err("No columns to fit");
+    }
+    if cols.iter().any(|c| c.len() != n) {
+        return Err("Columns are not all the same length");
+    }
+    if n < p {
+        return Err("More parameters than observations");
+    }
+
+    let mut a: Vec<Vec<f64>> = cols.to_vec();
+    let mut qty = y.to_vec();
+    let scale = a
+        .iter()
+        .map(|c| c.iter().map(|x| x * x).sum::<f64>().sqrt())
+        .fold(0.0, f64::max);
+
+    for k in 0..p {
+        let norm = a[k][k..].iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm <= 1e-10 * scale.max(1.0) {
+            return Err("Columns are linearly dependent");
+        }
+        let alpha = if a[k][k] > 0.0 { -norm } else { norm };
+        let mut v = a[k][k..].to_vec();
+        v[0] -= alpha;
+        let vv = v.iter().map(|x| x * x).sum::<f64>();
+        let reflect = |c: &mut [f64]| {
+            let d = v.iter().zip(c.iter()).map(|(a, b)| a * b).sum::<f64>() * 2.0 / vv;
+            for (c, v) in c.iter_mut().zip(v.iter()) {
+                *c -= d * v;
+            }
+        };
+        for col in a.iter_mut().skip(k) {
+            reflect(&mut col[k..]);
+        }
+        reflect(&mut qty[k..]);
+    }
+
+    // R is now the upper triangle, with a[j][i] holding R[i][j]
+    let mut coefs = vec![0.0; p];
+    for i in (0..p).rev() {
+        let s: f64 = (i + 1..p).map(|j| a[j][i] * coefs[j]).sum();
+        coefs[i] = (qty[i] - s) / a[i][i];
+    }
+
+    // Columns of R^-1
+    let rinv: Vec<Vec<f64>> = (0..p)
+        .map(|c| {
+            let mut col = vec![0.0; p];
+            for i in (0..=c).rev() {
+                let e = if i == c { 1.0 } else { 0.0 };
+                let s: f64 = (i + 1..=c).map(|j| a[j][i] * col[j]).sum();
+                col[i] = (e - s) / a[i][i];
+            }
+            col
+        })
+        .collect();
+    let xtx_inv = (0..p)
+        .map(|i| {
+            (0..p)
+                .map(|j| rinv.iter().map(|c| c[i] * c[j]).sum())
+                .collect()
+        })
+        .collect();
+
+    let fitted: Vec<f64> = (0..n)
+        .map(|i| cols.iter().zip(coefs.iter()).map(|(c, b)| c[i] * b).sum())
+        .collect();
+    let resid: Vec<f64> = y.iter().zip(fitted.iter()).map(|(y, f)| y - f).collect();
+    let rss = resid.iter().map(|r| r * r).sum();
+
+    Ok(Fit {
+        coefs,
+        fitted,
+        resid,
+        rss,
+        xtx_inv,
+    })
+}
@@ -1,5 +1,10 @@
 mod calcs;
 mod distrs;
+mod hyptest;
+mod linalg;
+mod power;
+mod rv;
+mod sim;
 
 use std::{
     fmt::{Debug, Display},
@@ -9,7 +14,7 @@ use std::{
 use calcs::OpenCrunchCalcs;
 use distrs::OpenCrunchCDistr;
 use eframe::App;
-use egui::{Id, Rect, Sense, TextEdit, Ui};
+use egui::{Grid, Id, Rect, ScrollArea, Sense, TextEdit, Ui};
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
@@ -98,9 +103,13 @@ impl App for OpenCrunch {
             }
             Active::Calcs => {
                 egui::panel::CentralPanel::default().show(ctx, |ui| {
-                    ui.add(&mut self.calcs);
+                    ScrollArea::vertical().show(ui, |ui| ui.add(&mut self.calcs));
                 });
                 f.set_window_title(&self.calcs.to_string());
+                if let Some(normal) = self.calcs.take_normal() {
+                    self.cdistr.show_normal(normal);
+                    self.active = Active::CDistr;
+                }
             }
             Active::None => {}
         }
@@ -159,6 +168,116 @@ impl GridNumBox for Ui {
     }
 }
 
+trait DataBox {
+    fn data_box(&mut self, l: &str, v: &mut String) -> egui::Response;
+}
+
+impl DataBox for Ui {
+    /// A multiline box for a column of data, one value per line. A text file dropped on it replaces its contents.
+    fn data_box(&mut self, l: &str, v: &mut String) -> egui::Response {
+        let mut resp = self
+            .vertical(|ui| {
+                ui.label(l);
+                ui.add(TextEdit::multiline(v).desired_rows(8).desired_width(100.))
+            })
+            .inner;
+        if let Some(text) = dropped_text(self, resp.rect) {
+            *v = text;
+            resp.mark_changed();
+        }
+        resp
+    }
+}
+
+/// Contents of a text file dropped onto `rect`.
+fn dropped_text(ui: &Ui, rect: egui::Rect) -> Option<String> {
+    let (pos, files) = ui
+        .ctx()
+        .input(|i| (i.pointer.hover_pos(), i.raw.dropped_files.clone()));
+    let file = files.first()?;
+    if !rect.contains(pos?) {
+        return None;
+    }
+    let bytes = match (&file.bytes, &file.path) {
+        (Some(bytes), _) => bytes.to_vec(),
+        (None, Some(path)) => std::fs::read(path).ok()?,
+        _ => return None,
+    };
+    String::from_utf8(bytes).ok()
+}
+
+trait TableView {
+    fn table(&mut self, id: &str, header: &[&str], rows: &[Vec<String>]) -> egui::Response;
+}
+
+impl TableView for Ui {
+    fn table(&mut self, id: &str, header: &[&str], rows: &[Vec<String>]) -> egui::Response {
+        Grid::new(id)
+            .striped(true)
+            .show(self, |ui| {
+                for h in header {
+                    ui.strong(*h);
+                }
+                ui.end_row();
+                for r in rows {
+                    for v in r {
+                        ui.label(v);
+                    }
+                    ui.end_row();
+                }
+            })
+            .response
+    }
+}
+
+/// Splits a column of data on whitespace, commas and semicolons, so pasted columns and rows both work.
+fn split_data(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|x| !x.is_empty())
+}
+
+/// Evaluates every entry in a data column.
+fn parse_data(s: &str) -> Result<Vec<f64>, String> {
+    split_data(s)
+        .map(|x| {
+            x.parse::<Expr>()
+                .ok()
+                .and_then(|e| e.eval().ok())
+                .ok_or_else(|| format!("{x} is not a number"))
+        })
+        .collect()
+}
+
+/// Reads a column of category labels.
+fn parse_labels(s: &str) -> Vec<String> {
+    split_data(s).map(|x| x.to_string()).collect()
+}
+
+/// Distinct labels in the order they first show up, and the level of each entry.
+fn levels(labels: &[String]) -> (Vec<String>, Vec<usize>) {
+    let mut names: Vec<String> = vec![];
+    let idx = labels
+        .iter()
+        .map(|l| match names.iter().position(|n| n == l) {
+            Some(i) => i,
+            None => {
+                names.push(l.clone());
+                names.len() - 1
+            }
+        })
+        .collect();
+    (names, idx)
+}
+
+/// Short form of a number for tables.
+fn fmt_num(x: f64) -> String {
+    if x != 0.0 && x.abs() < 0.0001 {
+        format!("{x:.3e}")
+    } else {
+        format!("{x:.4}")
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum Constr<T> {
     GE(T),
@@ -273,24 +392,24 @@ impl<T: std::str::FromStr + Debug> FromStr for Constr<T> {
     }
 }
 
-impl<T: Display> ToString for Constr<T> {
-    fn to_string(&self) -> String {
+impl<T: Display> Display for Constr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Constr::GE(v) => format!(">={v}"),
-            Constr::LE(v) => format!("<={v}"),
-            Constr::GT(v) => format!(">{v}"),
-            Constr::LT(v) => format!("<{v}"),
-            Constr::EQ(v) => format!("=={v}"),
-            Constr::NE(v) => format!("!={v}"),
-            Constr::In(a, b) => format!("[{a},{b}]"),
-            Constr::Out(a, b) => format!("]{a},{b}["),
-            Constr::None => "".to_string(),
-            Constr::GENone => ">=".to_string(),
-            Constr::GTNone => ">".to_string(),
-            Constr::LENone => "<=".to_string(),
-            Constr::LTNone => "<".to_string(),
-            Constr::EQNone => "=".to_string(),
-            Constr::NENone => "!=".to_string(),
+            Constr::GE(v) => write!(f, ">={v}"),
+            Constr::LE(v) => write!(f, "<={v}"),
+            Constr::GT(v) => write!(f, ">{v}"),
+            Constr::LT(v) => write!(f, "<{v}"),
+            Constr::EQ(v) => write!(f, "=={v}"),
+            Constr::NE(v) => write!(f, "!={v}"),
+            Constr::In(a, b) => write!(f, "[{a},{b}]"),
+            Constr::Out(a, b) => write!(f, "]{a},{b}["),
+            Constr::None => Ok(()),
+            Constr::GENone => write!(f, ">="),
+            Constr::GTNone => write!(f, ">"),
+            Constr::LENone => write!(f, "<="),
+            Constr::LTNone => write!(f, "<"),
+            Constr::EQNone => write!(f, "="),
+            Constr::NENone => write!(f, "!="),
         }
     }
 }
@@ -325,6 +444,19 @@ impl<T> Constr<T> {
         }
     }
 
+    /// The single bound of a one or two sided constraint.
+    fn point(&self) -> Option<&T> {
+        match self {
+            Constr::GE(n)
+            | Constr::LE(n)
+            | Constr::GT(n)
+            | Constr::LT(n)
+            | Constr::EQ(n)
+            | Constr::NE(n) => Some(n),
+            _ => None,
+        }
+    }
+
     fn is_ineq(&self) -> bool {
         matches!(
             self,
@@ -0,0 +1,427 @@
+use egui::{
+    plot::{HLine, Legend, Line, Plot, Points},
+    Color32, RichText, Ui, Widget,
+};
+use meval::Expr;
+use statrs::{
+    distribution::{
+        ChiSquared, Continuous, ContinuousCDF, Discrete, FisherSnedecor, Normal, Poisson, StudentsT,
+    },
+    function::beta::beta_reg,
+};
+
+use crate::{fmt_num, NumBox};
+
+/// Intervals used when integrating the non-central t.
+const NCT_STEPS: usize = 1000;
+/// Largest sample size searched for.
+const MAX_N: f64 = 1e7;
+
+/// P(T <= t) for a non-central t, integrating the normal over the scaled chi variate.
+fn nct_cdf(t: f64, df: f64, delta: f64) -> f64 {
+    let snd = Normal::new(0.0, 1.0).expect("SND cant fail");
+    if df.is_infinite() || df > 1e6 {
+        return snd.cdf(t - delta);
+    }
+    let chi = ChiSquared::new(df).expect("df is positive");
+    // V = sqrt(U / df) has density 2 df v f(df v^2)
+    let dens = |v: f64| {
+        if v <= 0.0 {
+            0.0
+        } else {
+            2.0 * df * v * chi.pdf(df * v * v)
+        }
+    };
+    let hi = 1.0 + 12.0 / (2.0 * df).sqrt();
+    let h = hi / NCT_STEPS as f64;
+    let f = |v: f64| snd.cdf(t * v - delta) * dens(v);
+    let mut sum = f(0.0) + f(hi);
+    for i in 1..NCT_STEPS {
+        sum += f(i as f64 * h) * if i % 2 == 0 { 2.0 } else { 4.0 };
+    }
+    (sum * h / 3.0).clamp(0.0, 1.0)
+}
+
+/// P(F <= x) for a non-central F, as a Poisson mixture of central F distributions.
+fn ncf_cdf(x: f64, d1: f64, d2: f64, lambda: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let y = d1 * x / (d1 * x + d2);
+    if lambda <= 0.0 {
+        return beta_reg(d1 / 2.0, d2 / 2.0, y);
+    }
+    let pois = Poisson::new(lambda / 2.0).expect("lambda is positive");
+    let mid = lambda / 2.0;
+    let end = (mid + 12.0 * mid.sqrt() + 20.0) as u64;
+    (0..=end)
+        .map(|j| pois.pmf(j) * beta_reg(d1 / 2.0 + j as f64, d2 / 2.0, y))
+        .sum::<f64>()
+        .clamp(0.0, 1.0)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum PowerTest {
+    #[default]
+    OneZ,
+    TwoZ,
+    OneT,
+    TwoT,
+    Prop,
+    Anova,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum Solve {
+    Effect,
+    Alpha,
+    Power,
+    #[default]
+    N,
+}
+
+#[derive(Clone)]
+pub(crate) struct Power {
+    test: PowerTest,
+    solve: Solve,
+    two_sided: bool,
+    curve: Vec<[f64; 2]>,
+    point: [f64; 2],
+    /// effect, alpha, power, n, groups, p0, error
+    strings: [String; 7],
+}
+
+impl Default for Power {
+    fn default() -> Self {
+        Self {
+            test: PowerTest::OneZ,
+            solve: Solve::N,
+            two_sided: true,
+            curve: vec![],
+            point: [0.0, 0.0],
+            strings: [
+                "0.5".to_string(),
+                "0.05".to_string(),
+                "0.8".to_string(),
+                "".to_string(),
+                "3".to_string(),
+                "0.5".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+fn eval(s: &str, name: &str) -> Result<f64, String> {
+    s.parse::<Expr>()
+        .ok()
+        .and_then(|e| e.eval().ok())
+        .ok_or(format!("{name} is invalid"))
+}
+
+/// Finds x in [lo, hi] where f crosses target, assuming f increases.
+fn bisect(f: impl Fn(f64) -> f64, target: f64, mut lo: f64, mut hi: f64) -> Option<f64> {
+    if f(hi) < target || f(lo) > target {
+        return None;
+    }
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if f(mid) < target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some((lo + hi) / 2.0)
+}
+
+impl Power {
+    fn effect_name(&self) -> &'static str {
+        match self.test {
+            PowerTest::OneZ | PowerTest::TwoZ | PowerTest::OneT | PowerTest::TwoT => {
+                "Effect size d"
+            }
+            PowerTest::Prop => "Alternative p1",
+            PowerTest::Anova => "Effect size f",
+        }
+    }
+
+    fn n_name(&self) -> &'static str {
+        match self.test {
+            PowerTest::OneZ | PowerTest::OneT | PowerTest::Prop => "n",
+            PowerTest::TwoZ | PowerTest::TwoT | PowerTest::Anova => "n per group",
+        }
+    }
+
+    /// Smallest n the test makes sense for.
+    fn min_n(&self) -> f64 {
+        match self.test {
+            PowerTest::OneZ | PowerTest::TwoZ | PowerTest::Prop => 1.0,
+            PowerTest::OneT | PowerTest::TwoT | PowerTest::Anova => 2.0,
+        }
+    }
+
+    /// Power against effect size e at level alpha with n observations (per group).
+    fn power(&self, e: f64, alpha: f64, n: f64, k: f64, p0: f64) -> f64 {
+        let snd = Normal::new(0.0, 1.0).expect("SND cant fail");
+        let tails = if self.two_sided { 2.0 } else { 1.0 };
+        match self.test {
+            PowerTest::OneZ | PowerTest::TwoZ => {
+                let delta = e.abs()
+                    * if self.test == PowerTest::OneZ {
+                        n.sqrt()
+                    } else {
+                        (n / 2.0).sqrt()
+                    };
+                let z = snd.inverse_cdf(1.0 - alpha / tails);
+                let mut p = 1.0 - snd.cdf(z - delta);
+                if self.two_sided {
+                    p += snd.cdf(-z - delta);
+                }
+                p
+            }
+            PowerTest::OneT | PowerTest::TwoT => {
+                let (df, delta) = if self.test == PowerTest::OneT {
+                    (n - 1.0, e.abs() * n.sqrt())
+                } else {
+                    (2.0 * n - 2.0, e.abs() * (n / 2.0).sqrt())
+                };
+                let Ok(dist) = StudentsT::new(0.0, 1.0, df) else {
+                    return 0.0;
+                };
+                let t = dist.inverse_cdf(1.0 - alpha / tails);
+                let mut p = 1.0 - nct_cdf(t, df, delta);
+                if self.two_sided {
+                    p += nct_cdf(-t, df, delta);
+                }
+                p
+            }
+            PowerTest::Prop => {
+                let diff = (e - p0).abs();
+                let se0 = (p0 * (1.0 - p0) / n).sqrt();
+                let se1 = (e * (1.0 - e) / n).sqrt();
+                let z = snd.inverse_cdf(1.0 - alpha / tails);
+                let mut p = snd.cdf((diff - z * se0) / se1);
+                if self.two_sided {
+                    p += snd.cdf((-diff - z * se0) / se1);
+                }
+                p
+            }
+            PowerTest::Anova => {
+                let (d1, d2) = (k - 1.0, k * (n - 1.0));
+                let Ok(dist) = FisherSnedecor::new(d1, d2) else {
+                    return 0.0;
+                };
+                let f = dist.inverse_cdf(1.0 - alpha);
+                1.0 - ncf_cdf(f, d1, d2, k * n * e * e)
+            }
+        }
+    }
+
+    fn calc(&mut self) -> Result<(), String> {
+        let k = if self.test == PowerTest::Anova {
+            let k = eval(&self.strings[4], "Groups")?;
+            if k < 2.0 || k.fract() != 0.0 {
+                return Err("Groups must be a whole number of at least 2".to_string());
+            }
+            k
+        } else {
+            1.0
+        };
+        let p0 = if self.test == PowerTest::Prop {
+            let p0 = eval(&self.strings[5], "p0")?;
+            if !(0.0..=1.0).contains(&p0) || p0 == 0.0 || p0 == 1.0 {
+                return Err("p0 must be between 0 and 1".to_string());
+            }
+            p0
+        } else {
+            0.0
+        };
+        let get = |i: usize, name: &str| eval(&self.strings[i], name);
+        let effect = || -> Result<f64, String> {
+            let e = get(0, self.effect_name())?;
+            if self.test == PowerTest::Prop {
+                if e <= 0.0 || e >= 1.0 || e == p0 {
+                    return Err("p1 must be between 0 and 1 and differ from p0".to_string());
+                }
+            } else if e == 0.0 {
+                return Err("Effect size can't be 0".to_string());
+            }
+            Ok(e)
+        };
+        let alpha = || -> Result<f64, String> {
+            let a = get(1, "Alpha")?;
+            if a <= 0.0 || a >= 1.0 {
+                return Err("Alpha must be between 0 and 1".to_string());
+            }
+            Ok(a)
+        };
+        let power = || -> Result<f64, String> {
+            let p = get(2, "Power")?;
+            if p <= 0.0 || p >= 1.0 {
+                return Err("Power must be between 0 and 1".to_string());
+            }
+            Ok(p)
+        };
+        let n = || -> Result<f64, String> {
+            let n = get(3, self.n_name())?;
+            if n < self.min_n() || n.fract() != 0.0 {
+                return Err(format!(
+                    "{} must be a whole number of at least {}",
+                    self.n_name(),
+                    self.min_n()
+                ));
+            }
+            Ok(n)
+        };
+
+        let (e, a, n) = match self.solve {
+            Solve::Power => {
+                let (e, a, n) = (effect()?, alpha()?, n()?);
+                self.strings[2] = fmt_num(self.power(e, a, n, k, p0));
+                (e, a, n)
+            }
+            Solve::N => {
+                let (e, a, p) = (effect()?, alpha()?, power()?);
+                let f = |n: f64| self.power(e, a, n, k, p0);
+                let mut hi = self.min_n();
+                while f(hi) < p {
+                    hi *= 2.0;
+                    if hi > MAX_N {
+                        return Err("Power can't be reached".to_string());
+                    }
+                }
+                let mut lo = (hi / 2.0).max(self.min_n());
+                while hi - lo > 1.0 {
+                    let mid = ((lo + hi) / 2.0).floor();
+                    if f(mid) < p {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                let n = if f(lo) >= p { lo } else { hi };
+                self.strings[3] = n.to_string();
+                (e, a, n)
+            }
+            Solve::Alpha => {
+                let (e, p, n) = (effect()?, power()?, n()?);
+                let a = bisect(|a| self.power(e, a, n, k, p0), p, 1e-12, 1.0 - 1e-12)
+                    .ok_or("Power can't be reached")?;
+                self.strings[1] = fmt_num(a);
+                (e, a, n)
+            }
+            Solve::Effect => {
+                let (a, p, n) = (alpha()?, power()?, n()?);
+                let e = if self.test == PowerTest::Prop {
+                    // Search for p1 above p0, unless that can't reach the power
+                    bisect(|e| self.power(e, a, n, k, p0), p, p0, 1.0 - 1e-12).or_else(|| {
+                        bisect(|e| self.power(p0 - e, a, n, k, p0), p, 0.0, p0 - 1e-12)
+                            .map(|e| p0 - e)
+                    })
+                } else {
+                    bisect(|e| self.power(e, a, n, k, p0), p, 0.0, 100.0)
+                }
+                .ok_or("Power can't be reached")?;
+                self.strings[0] = fmt_num(e);
+                (e, a, n)
+            }
+        };
+
+        let end = (n * 2.0).max(self.min_n() + 10.0);
+        let step = ((end - self.min_n()) / 100.0).max(1.0);
+        self.curve = (0..)
+            .map(|i| self.min_n() + i as f64 * step)
+            .take_while(|x| *x <= end)
+            .map(|x| [x, self.power(e, a, x, k, p0)])
+            .collect();
+        self.point = [n, self.power(e, a, n, k, p0)];
+        Ok(())
+    }
+}
+
+impl Widget for &mut Power {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut changed = ui
+            .horizontal(|ui| {
+                let mut changed = false;
+                for (t, name) in [
+                    (PowerTest::OneZ, "One-sample z"),
+                    (PowerTest::TwoZ, "Two-sample z"),
+                    (PowerTest::OneT, "One-sample t"),
+                    (PowerTest::TwoT, "Two-sample t"),
+                    (PowerTest::Prop, "Proportion"),
+                    (PowerTest::Anova, "One-way ANOVA"),
+                ] {
+                    changed |= ui.radio_value(&mut self.test, t, name).changed();
+                }
+                changed
+            })
+            .inner;
+        changed |= ui
+            .horizontal(|ui| {
+                ui.label("Solve for");
+                let mut changed = false;
+                for (s, name) in [
+                    (Solve::Effect, "effect"),
+                    (Solve::Alpha, "alpha"),
+                    (Solve::Power, "power"),
+                    (Solve::N, "n"),
+                ] {
+                    changed |= ui.radio_value(&mut self.solve, s, name).changed();
+                }
+                changed
+            })
+            .inner;
+        if self.test != PowerTest::Anova {
+            changed |= ui.checkbox(&mut self.two_sided, "Two sided").changed();
+        }
+
+        let mut resp = ui.label("");
+        if self.test == PowerTest::Prop {
+            resp = resp.union(ui.num_box("Null p0", &mut self.strings[5]));
+        }
+        if self.test == PowerTest::Anova {
+            resp = resp.union(ui.num_box("Groups", &mut self.strings[4]));
+        }
+        let labels = [self.effect_name(), "Alpha", "Power", self.n_name()];
+        let solved = self.solve as usize;
+        for (i, l) in labels.iter().enumerate() {
+            if i == solved {
+                ui.num_box(l, &mut self.strings[i].clone());
+            } else {
+                resp = resp.union(ui.num_box(l, &mut self.strings[i]));
+            }
+        }
+        if changed {
+            resp.mark_changed();
+        }
+        if resp.changed() {
+            self.curve.clear();
+            self.strings[6] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.label(RichText::new(&self.strings[6]).color(Color32::DARK_RED));
+
+        if !self.curve.is_empty() {
+            Plot::new("Power curve")
+                .height(250.)
+                .legend(Legend::default())
+                .include_y(0.0)
+                .include_y(1.0)
+                .show(ui, |ui| {
+                    ui.line(Line::new(self.curve.clone()).name("Power"));
+                    ui.points(
+                        Points::new(vec![self.point])
+                            .radius(4.)
+                            .color(Color32::RED)
+                            .name(self.n_name()),
+                    );
+                    ui.hline(HLine::new(self.point[1]).color(Color32::GRAY));
+                });
+        }
+        resp
+    }
+}
@@ -0,0 +1,584 @@
+use egui::{
+    plot::{Bar, BarChart, Legend, Line, Plot},
+    Color32, RichText, Ui, Widget,
+};
+use meval::Expr;
+use statrs::{
+    distribution::{Binomial, Continuous, ContinuousCDF, Discrete, DiscreteCDF, Normal, Poisson},
+    statistics::Distribution as _,
+};
+
+use crate::{fmt_num, parse_data, sim::eval, Constr, DataBox, GridNumBox, NumBox, TableView};
+
+/// How far the probabilities may sum from 1 and still count as a distribution.
+const PROB_TOL: f64 = 1e-6;
+
+/// A discrete random variable given as a table of values and their probabilities.
+#[derive(Clone)]
+pub(crate) struct DiscreteRv {
+    /// Values sorted ascending with their probabilities
+    pmf: Vec<(f64, f64)>,
+    event: Constr<f64>,
+    table: Vec<Vec<String>>,
+    /// values, probabilities, event, g(x), mean, variance, sd, P(event), E[g(X)], error
+    strings: [String; 10],
+}
+
+impl Default for DiscreteRv {
+    fn default() -> Self {
+        let mut rv = Self {
+            pmf: vec![],
+            event: Constr::None,
+            table: vec![],
+            strings: [
+                "0\n1\n2\n3".to_string(),
+                "0.1\n0.3\n0.4\n0.2".to_string(),
+                ">=2".to_string(),
+                "x^2".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        };
+        rv.strings[9] = rv.calc().err().unwrap_or_default();
+        rv
+    }
+}
+
+impl DiscreteRv {
+    fn calc(&mut self) -> Result<(), String> {
+        self.pmf.clear();
+        self.table.clear();
+        for s in self.strings[4..9].iter_mut() {
+            s.clear();
+        }
+        let values = parse_data(&self.strings[0])?;
+        let probs = parse_data(&self.strings[1])?;
+        if values.is_empty() {
+            return Err("Enter the values X can take".to_string());
+        }
+        if values.len() != probs.len() {
+            return Err("Need one probability for each value".to_string());
+        }
+        if probs.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            return Err("Probabilities must be between 0 and 1".to_string());
+        }
+        let total: f64 = probs.iter().sum();
+        if (total - 1.0).abs() > PROB_TOL {
+            return Err(format!("Probabilities sum to {}, not 1", fmt_num(total)));
+        }
+        let mut pmf: Vec<(f64, f64)> = values.into_iter().zip(probs).collect();
+        pmf.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if pmf.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err("Each value can only be listed once".to_string());
+        }
+        self.pmf = pmf;
+        let pmf = &self.pmf;
+
+        let mean: f64 = pmf.iter().map(|(x, p)| x * p).sum();
+        let var: f64 = pmf.iter().map(|(x, p)| (x - mean).powi(2) * p).sum();
+        self.strings[4] = fmt_num(mean);
+        self.strings[5] = fmt_num(var);
+        self.strings[6] = fmt_num(var.sqrt());
+        let mut cdf = 0.0;
+        for (x, p) in pmf {
+            cdf += p;
+            self.table
+                .push(vec![fmt_num(*x), fmt_num(*p), fmt_num(cdf.min(1.0))]);
+        }
+
+        self.event = self.strings[2].trim().parse().unwrap_or(Constr::None);
+        if self.event.is_some() {
+            let prob: f64 = pmf
+                .iter()
+                .filter(|(x, _)| self.event.comp(x))
+                .map(|(_, p)| p)
+                .sum();
+            self.strings[7] = fmt_num(prob);
+        } else if !self.strings[2].trim().is_empty() {
+            return Err("Event is invalid".to_string());
+        }
+
+        if !self.strings[3].trim().is_empty() {
+            let g = self.strings[3]
+                .parse::<Expr>()
+                .and_then(|e| e.bind("x"))
+                .map_err(|e| format!("g(x) is invalid: {e}"))?;
+            let eg: f64 = pmf.iter().map(|(x, p)| g(*x) * p).sum();
+            self.strings[8] = fmt_num(eg);
+        }
+        Ok(())
+    }
+}
+
+impl Widget for &mut DiscreteRv {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui
+            .horizontal(|ui| {
+                let resp = ui.data_box("x", &mut self.strings[0]);
+                resp.union(ui.data_box("P(X = x)", &mut self.strings[1]))
+            })
+            .inner;
+        resp = resp.union(ui.num_box("event for X", &mut self.strings[2]));
+        resp = resp.union(ui.num_box("g(x)", &mut self.strings[3]));
+        if resp.changed() {
+            self.strings[9] = self.calc().err().unwrap_or_default();
+        }
+        ui.num_box("E[X]", &mut self.strings[4].clone());
+        ui.num_box("Var(X)", &mut self.strings[5].clone());
+        ui.num_box("SD(X)", &mut self.strings[6].clone());
+        ui.num_box("P(event)", &mut self.strings[7].clone());
+        ui.num_box("E[g(X)]", &mut self.strings[8].clone());
+        ui.label(RichText::new(&self.strings[9]).color(Color32::DARK_RED));
+        if !self.table.is_empty() {
+            ui.table("discrete rv", &["x", "P(X = x)", "P(X <= x)"], &self.table);
+        }
+
+        if !self.pmf.is_empty() {
+            let gap = self
+                .pmf
+                .windows(2)
+                .map(|w| w[1].0 - w[0].0)
+                .fold(f64::INFINITY, f64::min);
+            let width = if gap.is_finite() { gap * 0.8 } else { 0.8 };
+            let (inside, outside): (Vec<Bar>, Vec<Bar>) = self
+                .pmf
+                .iter()
+                .map(|(x, p)| Bar::new(*x, *p).width(width))
+                .partition(|b| self.event.is_some() && self.event.comp(&b.argument));
+            Plot::new("Discrete pmf")
+                .height(300.)
+                .legend(Legend::default())
+                .show(ui, |ui| {
+                    ui.bar_chart(BarChart::new(outside).name("P(X = x)"));
+                    if !inside.is_empty() {
+                        ui.bar_chart(
+                            BarChart::new(inside)
+                                .color(Color32::LIGHT_RED)
+                                .name("In the event"),
+                        );
+                    }
+                });
+        }
+        resp
+    }
+}
+
+/// Mean and sd of a linear combination of random variables, exact when they are all normal.
+#[derive(Clone)]
+pub(crate) struct LinComb {
+    /// coefficient, mean, sd, copies for each variable
+    vars: Vec<[String; 4]>,
+    /// correlation between variables i and j at [i][j] for i < j
+    corr: Vec<Vec<String>>,
+    normal: bool,
+    result: Option<Normal>,
+    /// Set when the user asks to open the result in the distributions tab
+    send: Option<Normal>,
+    /// constant, mean, variance, sd, error
+    strings: [String; 5],
+}
+
+impl Default for LinComb {
+    fn default() -> Self {
+        let mut lc = Self {
+            vars: vec![
+                [
+                    "1".to_string(),
+                    "10".to_string(),
+                    "2".to_string(),
+                    "1".to_string(),
+                ],
+                [
+                    "-1".to_string(),
+                    "8".to_string(),
+                    "1.5".to_string(),
+                    "1".to_string(),
+                ],
+            ],
+            corr: vec![vec!["".to_string(); 2]; 2],
+            normal: true,
+            result: None,
+            send: None,
+            strings: [
+                "0".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        };
+        lc.strings[4] = lc.calc().err().unwrap_or_default();
+        lc
+    }
+}
+
+impl LinComb {
+    /// The normal the user chose to open in the distributions tab, if any.
+    pub(crate) fn take_send(&mut self) -> Option<Normal> {
+        self.send.take()
+    }
+
+    fn calc(&mut self) -> Result<(), String> {
+        self.result = None;
+        for s in self.strings[1..4].iter_mut() {
+            s.clear();
+        }
+        let c = eval(&self.strings[0], "constant")?;
+        let mut vars = Vec::with_capacity(self.vars.len());
+        for (i, v) in self.vars.iter().enumerate() {
+            let name = format!("X{}", i + 1);
+            let a = eval(&v[0], &format!("coefficient of {name}"))?;
+            let mean = eval(&v[1], &format!("mean of {name}"))?;
+            let sd = eval(&v[2], &format!("sd of {name}"))?;
+            if sd < 0.0 {
+                return Err(format!("sd of {name} can't be negative"));
+            }
+            let copies = if v[3].trim().is_empty() {
+                1.0
+            } else {
+                eval(&v[3], &format!("copies of {name}"))?
+            };
+            if copies < 1.0 || copies.fract() != 0.0 {
+                return Err(format!("copies of {name} must be a positive whole number"));
+            }
+            vars.push((a, mean, sd, copies));
+        }
+
+        // Each variable stands for a sum of iid copies, so its variance grows with the count
+        let mut mean = c;
+        let mut var = 0.0;
+        for (a, m, sd, n) in &vars {
+            mean += a * n * m;
+            var += a * a * n * sd * sd;
+        }
+        for i in 0..vars.len() {
+            for j in i + 1..vars.len() {
+                let s = &self.corr[i][j];
+                if s.trim().is_empty() {
+                    continue;
+                }
+                let rho = eval(s, &format!("correlation of X{} and X{}", i + 1, j + 1))?;
+                if !(-1.0..=1.0).contains(&rho) {
+                    return Err("Correlations must be between -1 and 1".to_string());
+                }
+                if rho == 0.0 {
+                    continue;
+                }
+                let (ai, _, si, ni) = vars[i];
+                let (aj, _, sj, nj) = vars[j];
+                if ni != 1.0 || nj != 1.0 {
+                    return Err("Correlated variables must have one copy".to_string());
+                }
+                var += 2.0 * ai * aj * rho * si * sj;
+            }
+        }
+        if var < -1e-12 {
+            return Err("These correlations can't all hold at once".to_string());
+        }
+        let var = var.max(0.0);
+        self.strings[1] = fmt_num(mean);
+        self.strings[2] = fmt_num(var);
+        self.strings[3] = fmt_num(var.sqrt());
+        if self.normal && var > 0.0 {
+            self.result = Normal::new(mean, var.sqrt()).ok();
+        }
+        Ok(())
+    }
+}
+
+impl Widget for &mut LinComb {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.label("Y = c + a1 (X1 sum of copies) + a2 (X2 sum of copies) + ...");
+        let respm = resp.clone();
+        ui.horizontal(|ui| {
+            ui.add_space(40.);
+            for h in ["a", "mean", "sd", "copies"] {
+                ui.add_sized((60., 20.), egui::Label::new(h));
+            }
+        });
+        for (i, v) in self.vars.iter_mut().enumerate() {
+            resp = resp.union(
+                ui.horizontal(|ui| {
+                    ui.add_sized((32., 20.), egui::Label::new(format!("X{}", i + 1)));
+                    let mut resp = respm.clone();
+                    for s in v.iter_mut() {
+                        resp = resp.union(ui.grid_num_box(60, s));
+                    }
+                    resp
+                })
+                .inner,
+            );
+        }
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("Add variable").clicked() {
+                self.vars.push([
+                    "1".to_string(),
+                    "0".to_string(),
+                    "1".to_string(),
+                    "1".to_string(),
+                ]);
+                changed = true;
+            }
+            if self.vars.len() > 1 && ui.button("Remove variable").clicked() {
+                self.vars.pop();
+                changed = true;
+            }
+        });
+        let k = self.vars.len();
+        self.corr.resize(k, vec![]);
+        for row in self.corr.iter_mut() {
+            row.resize(k, String::new());
+        }
+        if k > 1 {
+            ui.label("Correlations (blank for independent)");
+            for i in 0..k - 1 {
+                resp = resp.union(
+                    ui.horizontal(|ui| {
+                        ui.add_sized((32., 20.), egui::Label::new(format!("X{}", i + 1)));
+                        let mut resp = respm.clone();
+                        for j in 1..k {
+                            if j > i {
+                                resp = resp.union(ui.grid_num_box(60, &mut self.corr[i][j]));
+                            } else {
+                                ui.add_space(68.);
+                            }
+                        }
+                        resp
+                    })
+                    .inner,
+                );
+            }
+        }
+        resp = resp.union(ui.num_box("constant c", &mut self.strings[0]));
+        changed |= ui
+            .checkbox(&mut self.normal, "All variables are normal")
+            .changed();
+        if changed {
+            resp.mark_changed();
+        }
+        if resp.changed() {
+            self.strings[4] = self.calc().err().unwrap_or_default();
+        }
+        ui.num_box("E[Y]", &mut self.strings[1].clone());
+        ui.num_box("Var(Y)", &mut self.strings[2].clone());
+        ui.num_box("SD(Y)", &mut self.strings[3].clone());
+        if let Some(n) = self.result {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Y ~ Normal({}, {})",
+                    fmt_num(n.mean().unwrap_or(f64::NAN)),
+                    fmt_num(n.std_dev().unwrap_or(f64::NAN))
+                ));
+                if ui.button("Open in Distributions").clicked() {
+                    self.send = Some(n);
+                }
+            });
+        }
+        ui.label(RichText::new(&self.strings[4]).color(Color32::DARK_RED));
+        resp
+    }
+}
+
+/// Largest number of trials, so the exact sum stays quick enough to rerun on every edit.
+const MAX_TRIALS: f64 = 1e7;
+
+/// Integer ranges, inclusive and possibly unbounded, that a constraint on a count covers.
+fn int_ranges(event: &Constr<f64>) -> Result<Vec<(f64, f64)>, String> {
+    let inf = f64::INFINITY;
+    let ranges = match *event {
+        Constr::LE(v) => vec![(-inf, v.floor())],
+        Constr::LT(v) => vec![(-inf, v.ceil() - 1.0)],
+        Constr::GE(v) => vec![(v.ceil(), inf)],
+        Constr::GT(v) => vec![(v.floor() + 1.0, inf)],
+        Constr::EQ(v) if v.fract() == 0.0 => vec![(v, v)],
+        Constr::EQ(_) => vec![],
+        Constr::NE(v) if v.fract() == 0.0 => vec![(-inf, v - 1.0), (v + 1.0, inf)],
+        Constr::NE(_) => vec![(-inf, inf)],
+        Constr::In(a, b) => vec![(a.ceil(), b.floor())],
+        Constr::Out(a, b) => vec![(-inf, a.ceil() - 1.0), (b.floor() + 1.0, inf)],
+        _ => return Err("Event on k is invalid".to_string()),
+    };
+    Ok(ranges.into_iter().filter(|(lo, hi)| lo <= hi).collect())
+}
+
+/// Exact binomial next to its normal and Poisson approximations.
+#[derive(Clone)]
+pub(crate) struct NormalApprox {
+    event: Constr<f64>,
+    table: Vec<Vec<String>>,
+    /// k and P(X = k) for each bar shown
+    bars: Vec<[f64; 2]>,
+    curve: Vec<[f64; 2]>,
+    /// trials, p, event, mean, sd, warning, error
+    strings: [String; 7],
+}
+
+impl Default for NormalApprox {
+    fn default() -> Self {
+        let mut na = Self {
+            event: Constr::None,
+            table: vec![],
+            bars: vec![],
+            curve: vec![],
+            strings: [
+                "20".to_string(),
+                "0.3".to_string(),
+                "<=4".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        };
+        na.strings[6] = na.calc().err().unwrap_or_default();
+        na
+    }
+}
+
+impl NormalApprox {
+    fn calc(&mut self) -> Result<(), String> {
+        self.table.clear();
+        self.bars.clear();
+        self.curve.clear();
+        for s in self.strings[3..6].iter_mut() {
+            s.clear();
+        }
+        let n = eval(&self.strings[0], "trials")?;
+        let p = eval(&self.strings[1], "p")?;
+        if n < 1.0 || n.fract() != 0.0 || n > MAX_TRIALS {
+            return Err(format!(
+                "trials must be a whole number from 1 to {MAX_TRIALS}"
+            ));
+        }
+        if !(0.0 < p && p < 1.0) {
+            return Err("p must be between 0 and 1".to_string());
+        }
+        self.event = self.strings[2]
+            .trim()
+            .parse()
+            .map_err(|_| "Event on k is invalid".to_string())?;
+        let ranges = int_ranges(&self.event)?;
+
+        let bin = Binomial::new(p, n as u64).map_err(|e| e.to_string())?;
+        let pois = Poisson::new(n * p).map_err(|e| e.to_string())?;
+        let mean = n * p;
+        let sd = (n * p * (1.0 - p)).sqrt();
+        let normal = Normal::new(mean, sd).map_err(|e| e.to_string())?;
+        self.strings[3] = fmt_num(mean);
+        self.strings[4] = fmt_num(sd);
+        if n * p < 10.0 || n * (1.0 - p) < 10.0 {
+            self.strings[5] =
+                "np or n(1 - p) is under 10, the normal approximation may be poor".to_string();
+        }
+
+        // P(X <= k) for a count, which is 0 below 0 and 1 at infinity
+        let count_cdf = |k: f64, cdf: &dyn Fn(u64) -> f64| {
+            if k < 0.0 {
+                0.0
+            } else if k.is_infinite() {
+                1.0
+            } else {
+                cdf(k as u64)
+            }
+        };
+        let bin_cdf = |k: u64| bin.cdf(k);
+        let pois_cdf = |k: u64| pois.cdf(k);
+        let sum = |f: &dyn Fn(f64, f64) -> f64| -> f64 {
+            ranges.iter().map(|(lo, hi)| f(*lo, *hi)).sum()
+        };
+        let exact = sum(&|lo, hi| count_cdf(hi, &bin_cdf) - count_cdf(lo - 1.0, &bin_cdf));
+        // The uncorrected curve reads the event's own bounds, a single value having no area
+        let plain = match self.event {
+            Constr::LE(v) | Constr::LT(v) => normal.cdf(v),
+            Constr::GE(v) | Constr::GT(v) => 1.0 - normal.cdf(v),
+            Constr::EQ(_) => 0.0,
+            Constr::NE(_) => 1.0,
+            Constr::In(a, b) => normal.cdf(b) - normal.cdf(a),
+            Constr::Out(a, b) => normal.cdf(a) + 1.0 - normal.cdf(b),
+            _ => unreachable!("int_ranges rejects other events"),
+        };
+        let corrected = sum(&|lo, hi| normal.cdf(hi + 0.5) - normal.cdf(lo - 0.5));
+        let poisson = sum(&|lo, hi| count_cdf(hi, &pois_cdf) - count_cdf(lo - 1.0, &pois_cdf));
+        self.table.push(vec![
+            "Exact binomial".to_string(),
+            fmt_num(exact),
+            "".to_string(),
+        ]);
+        for (name, approx) in [
+            ("Normal", plain),
+            ("Normal with continuity correction", corrected),
+            ("Poisson", poisson),
+        ] {
+            self.table.push(vec![
+                name.to_string(),
+                fmt_num(approx),
+                fmt_num(approx - exact),
+            ]);
+        }
+
+        let lo = (mean - 5.0 * sd).floor().max(0.0);
+        let hi = (mean + 5.0 * sd).ceil().min(n);
+        self.bars = (lo as u64..=hi as u64)
+            .map(|k| [k as f64, bin.pmf(k)])
+            .collect();
+        self.curve = (0..=200)
+            .map(|i| lo - 0.5 + (hi - lo + 1.0) * i as f64 / 200.0)
+            .map(|x| [x, normal.pdf(x)])
+            .collect();
+        Ok(())
+    }
+}
+
+impl Widget for &mut NormalApprox {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = ui.num_box("trials n", &mut self.strings[0]);
+        resp = resp.union(ui.num_box("p", &mut self.strings[1]));
+        resp = resp.union(ui.num_box("event for k", &mut self.strings[2]));
+        if resp.changed() {
+            self.strings[6] = self.calc().err().unwrap_or_default();
+        }
+        ui.num_box("mean np", &mut self.strings[3].clone());
+        ui.num_box("sd", &mut self.strings[4].clone());
+        if !self.table.is_empty() {
+            ui.table(
+                "normal approx",
+                &["Method", "P(event)", "Error"],
+                &self.table,
+            );
+        }
+        ui.label(RichText::new(&self.strings[5]).color(Color32::GOLD));
+        ui.label(RichText::new(&self.strings[6]).color(Color32::DARK_RED));
+
+        if !self.bars.is_empty() {
+            let (inside, outside): (Vec<Bar>, Vec<Bar>) = self
+                .bars
+                .iter()
+                .map(|[k, h]| Bar::new(*k, *h).width(0.9))
+                .partition(|b| self.event.comp(&b.argument));
+            Plot::new("Normal approximation")
+                .height(300.)
+                .legend(Legend::default())
+                .show(ui, |ui| {
+                    ui.bar_chart(BarChart::new(outside).name("Binomial"));
+                    if !inside.is_empty() {
+                        ui.bar_chart(
+                            BarChart::new(inside)
+                                .color(Color32::LIGHT_RED)
+                                .name("In the event"),
+                        );
+                    }
+                    ui.line(
+                        Line::new(self.curve.clone())
+                            .color(Color32::RED)
+                            .name("Normal"),
+                    );
+                });
+        }
+        resp
+    }
+}
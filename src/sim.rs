@@ -0,0 +1,1306 @@
+use egui::{
+    plot::{Bar, BarChart, Legend, Line, Plot, VLine},
+    Color32, RichText, Slider, Ui, Widget,
+};
+use meval::Expr;
+use rand::{distributions::Distribution, seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use statrs::{
+    distribution::{
+        Binomial, ChiSquared, Continuous, ContinuousCDF, Exp, FisherSnedecor, Geometric, Normal,
+        Poisson, StudentsT, Uniform,
+    },
+    statistics::Distribution as _,
+};
+
+use crate::{
+    calcs::{for_each_combination, for_each_permutation, mean_dist, pearson, AltRadio},
+    fmt_num,
+    hyptest::{Alternative, NullDist},
+    parse_data, Constr, DataBox, NumBox, TableView,
+};
+
+/// Most values drawn in one simulation, so it stays quick enough to rerun on every edit.
+const MAX_DRAWS: usize = 5_000_000;
+/// Most intervals drawn on the coverage plot.
+const MAX_SHOWN: usize = 200;
+/// Most arrangements a permutation test enumerates exactly.
+const EXACT_PERM_MAX: f64 = 100_000.0;
+/// Share cut from each end for the trimmed mean.
+const TRIM: f64 = 0.1;
+/// Names a statistic can use, in the order `sample_vars` gives them.
+const STAT_VARS: [&str; 9] = ["x", "n", "sum", "mean", "median", "var", "sd", "min", "max"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub(crate) enum Family {
+    #[default]
+    Normal,
+    Uniform,
+    Exponential,
+    T,
+    ChiSquared,
+    F,
+    Binomial,
+    Poisson,
+    Geometric,
+}
+
+impl Family {
+    pub(crate) const ALL: [(Family, &'static str); 9] = [
+        (Family::Normal, "Normal"),
+        (Family::Uniform, "Uniform"),
+        (Family::Exponential, "Exponential"),
+        (Family::T, "T"),
+        (Family::ChiSquared, "Chi Square"),
+        (Family::F, "F"),
+        (Family::Binomial, "Binomial"),
+        (Family::Poisson, "Poisson"),
+        (Family::Geometric, "Geometric"),
+    ];
+
+    /// Names of the parameters, the second is empty when there is only one.
+    pub(crate) fn params(&self) -> [&'static str; 2] {
+        match self {
+            Family::Normal => ["mean", "sd"],
+            Family::Uniform => ["min", "max"],
+            Family::Exponential => ["rate", ""],
+            Family::T | Family::ChiSquared => ["df", ""],
+            Family::F => ["df 1", "df 2"],
+            Family::Binomial => ["trials", "p"],
+            Family::Poisson => ["mean", ""],
+            Family::Geometric => ["p", ""],
+        }
+    }
+
+    pub(crate) fn defaults(&self) -> [&'static str; 2] {
+        match self {
+            Family::Normal => ["0", "1"],
+            Family::Uniform => ["0", "1"],
+            Family::Exponential => ["1", ""],
+            Family::T => ["10", ""],
+            Family::ChiSquared => ["4", ""],
+            Family::F => ["4", "10"],
+            Family::Binomial => ["10", "0.5"],
+            Family::Poisson => ["3", ""],
+            Family::Geometric => ["0.3", ""],
+        }
+    }
+}
+
+/// A population to draw from, continuous, discrete or a column of data drawn with replacement.
+#[derive(Clone, Debug)]
+pub(crate) enum Population {
+    Normal(Normal),
+    Uniform(Uniform),
+    Exponential(Exp),
+    T(StudentsT),
+    ChiSquared(ChiSquared),
+    F(FisherSnedecor),
+    Binomial(Binomial),
+    Poisson(Poisson),
+    Geometric(Geometric),
+    Data(Vec<f64>),
+}
+
+impl Population {
+    pub(crate) fn new(family: Family, a: f64, b: f64) -> Result<Self, String> {
+        let err = |e: statrs::StatsError| e.to_string();
+        Ok(match family {
+            Family::Normal => Population::Normal(Normal::new(a, b).map_err(err)?),
+            Family::Uniform => Population::Uniform(Uniform::new(a, b).map_err(err)?),
+            Family::Exponential => Population::Exponential(Exp::new(a).map_err(err)?),
+            Family::T => Population::T(StudentsT::new(0.0, 1.0, a).map_err(err)?),
+            Family::ChiSquared => Population::ChiSquared(ChiSquared::new(a).map_err(err)?),
+            Family::F => Population::F(FisherSnedecor::new(a, b).map_err(err)?),
+            Family::Binomial => {
+                if a < 0.0 || a.fract() != 0.0 {
+                    return Err("Trials must be a whole number".to_string());
+                }
+                Population::Binomial(Binomial::new(b, a as u64).map_err(err)?)
+            }
+            Family::Poisson => Population::Poisson(Poisson::new(a).map_err(err)?),
+            Family::Geometric => Population::Geometric(Geometric::new(a).map_err(err)?),
+        })
+    }
+
+    /// One value from the population.
+    pub(crate) fn draw(&self, rng: &mut SimRng) -> f64 {
+        match self {
+            Population::Normal(d) => d.sample(rng),
+            Population::Uniform(d) => d.sample(rng),
+            Population::Exponential(d) => d.sample(rng),
+            Population::T(d) => d.sample(rng),
+            Population::ChiSquared(d) => d.sample(rng),
+            Population::F(d) => d.sample(rng),
+            Population::Binomial(d) => d.sample(rng),
+            Population::Poisson(d) => d.sample(rng),
+            Population::Geometric(d) => d.sample(rng),
+            Population::Data(v) => v[rng.gen_range(0..v.len())],
+        }
+    }
+
+    pub(crate) fn mean(&self) -> Option<f64> {
+        match self {
+            Population::Normal(d) => d.mean(),
+            Population::Uniform(d) => d.mean(),
+            Population::Exponential(d) => d.mean(),
+            Population::T(d) => d.mean(),
+            Population::ChiSquared(d) => d.mean(),
+            Population::F(d) => d.mean(),
+            Population::Binomial(d) => d.mean(),
+            Population::Poisson(d) => d.mean(),
+            Population::Geometric(d) => d.mean(),
+            Population::Data(v) => Some(v.iter().sum::<f64>() / v.len() as f64),
+        }
+    }
+
+    pub(crate) fn sd(&self) -> Option<f64> {
+        match self {
+            Population::Normal(d) => d.std_dev(),
+            Population::Uniform(d) => d.std_dev(),
+            Population::Exponential(d) => d.std_dev(),
+            Population::T(d) => d.std_dev(),
+            Population::ChiSquared(d) => d.std_dev(),
+            Population::F(d) => d.std_dev(),
+            Population::Binomial(d) => d.std_dev(),
+            Population::Poisson(d) => d.std_dev(),
+            Population::Geometric(d) => d.std_dev(),
+            Population::Data(v) => {
+                let mean = self.mean()?;
+                let var = v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / v.len() as f64;
+                Some(var.sqrt())
+            }
+        }
+    }
+
+    /// A sample of n values.
+    pub(crate) fn sample(&self, rng: &mut SimRng, n: usize) -> Vec<f64> {
+        (0..n).map(|_| self.draw(rng)).collect()
+    }
+}
+
+/// Reads the parameters of a family from its two boxes.
+fn population(family: Family, params: &[String]) -> Result<Population, String> {
+    let [p1, p2] = family.params();
+    let a = eval(&params[0], p1)?;
+    let b = if p2.is_empty() {
+        0.0
+    } else {
+        eval(&params[1], p2)?
+    };
+    Population::new(family, a, b)
+}
+
+/// Family radios and parameter boxes, putting in the defaults when the family changes.
+fn family_ui(ui: &mut Ui, family: &mut Family, params: &mut [String]) -> egui::Response {
+    let changed = ui
+        .horizontal_wrapped(|ui| {
+            let mut changed = false;
+            for (f, name) in Family::ALL {
+                if ui.radio_value(family, f, name).changed() {
+                    for (s, d) in params.iter_mut().zip(f.defaults()) {
+                        *s = d.to_string();
+                    }
+                    changed = true;
+                }
+            }
+            changed
+        })
+        .inner;
+    let [p1, p2] = family.params();
+    let mut resp = ui.num_box(p1, &mut params[0]);
+    if !p2.is_empty() {
+        resp = resp.union(ui.num_box(p2, &mut params[1]));
+    }
+    if changed {
+        resp.mark_changed();
+    }
+    resp
+}
+
+/// The seed box with a button for a fresh seed, changed when either is used.
+fn seed_ui(ui: &mut Ui, seed: &mut String) -> egui::Response {
+    ui.horizontal(|ui| {
+        let mut resp = ui.num_box("seed", seed);
+        if ui.button("New seed").clicked() {
+            *seed = new_seed().to_string();
+            resp.mark_changed();
+        }
+        resp
+    })
+    .inner
+}
+
+fn parse_seed(s: &str) -> Result<u64, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| "Seed must be a whole number".to_string())
+}
+
+/// The simulation generator. ChaCha8 is a fixed algorithm, unlike `StdRng` which rand
+/// may change between releases, so a seed keeps giving the same draws.
+pub(crate) type SimRng = ChaCha8Rng;
+
+/// A generator that gives the same draws every time for the same seed.
+pub(crate) fn seeded(seed: u64) -> SimRng {
+    SimRng::seed_from_u64(seed)
+}
+
+/// A seed from the system's randomness, for when a fresh run is wanted.
+pub(crate) fn new_seed() -> u64 {
+    let mut buf = [0u8; 8];
+    getrandom::getrandom(&mut buf).expect("System randomness is available");
+    u64::from_le_bytes(buf) % 1_000_000
+}
+
+/// Runs an experiment `reps` times, one after another from a single seed.
+pub(crate) fn simulate<T>(
+    seed: u64,
+    reps: usize,
+    mut experiment: impl FnMut(&mut SimRng) -> T,
+) -> Vec<T> {
+    let mut rng = seeded(seed);
+    (0..reps).map(|_| experiment(&mut rng)).collect()
+}
+
+/// Share of experiments where the event happened, and its Monte Carlo standard error.
+pub(crate) fn proportion(hits: &[bool]) -> (f64, f64) {
+    let n = hits.len() as f64;
+    let p = hits.iter().filter(|h| **h).count() as f64 / n;
+    (p, (p * (1.0 - p) / n).sqrt())
+}
+
+/// Values of `STAT_VARS` for a sample.
+fn sample_vars(v: &[f64]) -> [f64; 9] {
+    let n = v.len() as f64;
+    let sum: f64 = v.iter().sum();
+    let mean = sum / n;
+    let var = v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let mut sorted = v.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    };
+    [
+        v[0],
+        n,
+        sum,
+        mean,
+        median,
+        var,
+        var.sqrt(),
+        sorted[0],
+        sorted[sorted.len() - 1],
+    ]
+}
+
+/// Density scaled histogram bars, as bar centers and heights, and the bar width.
+fn histogram(values: &[f64]) -> (Vec<[f64; 2]>, f64) {
+    let lo = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let bins = (values.len() as f64).sqrt().ceil().min(60.0) as usize;
+    let width = if hi > lo {
+        (hi - lo) / bins as f64
+    } else {
+        1.0
+    };
+    let mut counts = vec![0usize; bins];
+    for v in values {
+        counts[(((v - lo) / width) as usize).min(bins - 1)] += 1;
+    }
+    let bars = counts
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            [
+                lo + (i as f64 + 0.5) * width,
+                *c as f64 / (values.len() as f64 * width),
+            ]
+        })
+        .collect();
+    (bars, width)
+}
+
+/// Value at proportion p of sorted values, interpolating between neighbours.
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let h = (sorted.len() - 1) as f64 * p.clamp(0.0, 1.0);
+    let i = h.floor() as usize;
+    match sorted.get(i + 1) {
+        Some(next) => sorted[i] + (h - i as f64) * (next - sorted[i]),
+        None => sorted[i],
+    }
+}
+
+pub(crate) fn eval(s: &str, name: &str) -> Result<f64, String> {
+    s.parse::<Expr>()
+        .ok()
+        .and_then(|e| e.eval().ok())
+        .ok_or(format!("{name} is invalid"))
+}
+
+/// Estimates the chance of an event on a statistic of repeated samples.
+#[derive(Clone)]
+pub(crate) struct MonteCarlo {
+    family: Family,
+    /// param 1, param 2, sample size, experiments, seed, statistic, event,
+    /// probability, standard error, statistic mean, error
+    strings: [String; 11],
+}
+
+impl Default for MonteCarlo {
+    fn default() -> Self {
+        Self {
+            family: Family::Normal,
+            strings: [
+                "0".to_string(),
+                "1".to_string(),
+                "1".to_string(),
+                "10000".to_string(),
+                "1".to_string(),
+                "x".to_string(),
+                ">1.96".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+impl MonteCarlo {
+    fn calc(&mut self) -> Result<(), String> {
+        let pop = population(self.family, &self.strings[..2])?;
+        let n = eval(&self.strings[2], "Sample size")?;
+        let reps = eval(&self.strings[3], "Experiments")?;
+        if n < 1.0 || n.fract() != 0.0 || reps < 1.0 || reps.fract() != 0.0 {
+            return Err("Sample size and experiments must be positive whole numbers".to_string());
+        }
+        let (n, reps) = (n as usize, reps as usize);
+        if n.saturating_mul(reps) > MAX_DRAWS {
+            return Err(format!("At most {MAX_DRAWS} values can be drawn"));
+        }
+        let seed = parse_seed(&self.strings[4])?;
+        let stat = self.strings[5]
+            .parse::<Expr>()
+            .and_then(|e| e.bindn(&STAT_VARS))
+            .map_err(|e| format!("Statistic is invalid: {e}"))?;
+        let event = self.strings[6]
+            .trim()
+            .parse::<Constr<f64>>()
+            .map_err(|_| "Event is invalid, use something like >1.96 or [0,2]")?;
+
+        let values = simulate(seed, reps, |rng| stat(&sample_vars(&pop.sample(rng, n))));
+        let hits: Vec<bool> = values.iter().map(|v| event.comp(v)).collect();
+        let (p, se) = proportion(&hits);
+        let mean = values.iter().sum::<f64>() / reps as f64;
+        self.strings[7] = fmt_num(p);
+        self.strings[8] = fmt_num(se);
+        self.strings[9] = fmt_num(mean);
+        Ok(())
+    }
+}
+
+impl Widget for &mut MonteCarlo {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = family_ui(ui, &mut self.family, &mut self.strings[..2]);
+        resp = resp.union(ui.num_box("sample size", &mut self.strings[2]));
+        resp = resp.union(ui.num_box("experiments", &mut self.strings[3]));
+        resp = resp.union(seed_ui(ui, &mut self.strings[4]));
+        ui.label(
+            "The statistic can use x (the first value), n, sum, mean, median, var, sd, min and max",
+        );
+        resp = resp.union(ui.num_box("statistic", &mut self.strings[5]));
+        resp = resp.union(ui.num_box("event", &mut self.strings[6]));
+        if resp.changed() {
+            self.strings[10] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => {
+                    self.strings[7..10].iter_mut().for_each(String::clear);
+                    e
+                }
+            };
+        }
+        ui.num_box("P(event)", &mut self.strings[7].clone());
+        ui.num_box("MC std error", &mut self.strings[8].clone());
+        ui.num_box("mean of statistic", &mut self.strings[9].clone());
+        ui.label(RichText::new(&self.strings[10]).color(Color32::DARK_RED));
+        resp
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum SampleStatistic {
+    #[default]
+    Mean,
+    Median,
+    Variance,
+}
+
+/// Histogram of a statistic over many simulated samples, against the normal the CLT predicts for the mean.
+#[derive(Clone)]
+pub(crate) struct SamplingDist {
+    family: Family,
+    from_data: bool,
+    statistic: SampleStatistic,
+    n: usize,
+    bars: Vec<[f64; 2]>,
+    width: f64,
+    curve: Vec<[f64; 2]>,
+    /// param 1, param 2, data, experiments, seed, simulated mean, simulated sd,
+    /// normal mean, normal sd, error
+    strings: [String; 10],
+}
+
+impl Default for SamplingDist {
+    fn default() -> Self {
+        Self {
+            family: Family::Exponential,
+            from_data: false,
+            statistic: SampleStatistic::Mean,
+            n: 5,
+            bars: vec![],
+            width: 1.0,
+            curve: vec![],
+            strings: [
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "2000".to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+impl SamplingDist {
+    fn calc(&mut self) -> Result<(), String> {
+        self.bars.clear();
+        self.curve.clear();
+        self.strings[5..9].iter_mut().for_each(String::clear);
+        let pop = if self.from_data {
+            let v = parse_data(&self.strings[2])?;
+            if v.is_empty() {
+                return Err("Enter the population data".to_string());
+            }
+            Population::Data(v)
+        } else {
+            population(self.family, &self.strings[..2])?
+        };
+        let reps = eval(&self.strings[3], "Experiments")?;
+        if reps < 2.0 || reps.fract() != 0.0 {
+            return Err("Experiments must be a whole number of at least 2".to_string());
+        }
+        let (n, reps) = (self.n, reps as usize);
+        if n.saturating_mul(reps) > MAX_DRAWS {
+            return Err(format!("At most {MAX_DRAWS} values can be drawn"));
+        }
+        if self.statistic == SampleStatistic::Variance && n < 2 {
+            return Err("Sample variances need n of at least 2".to_string());
+        }
+        let seed = parse_seed(&self.strings[4])?;
+        // Position in sample_vars
+        let idx = match self.statistic {
+            SampleStatistic::Mean => 3,
+            SampleStatistic::Median => 4,
+            SampleStatistic::Variance => 5,
+        };
+        let values = simulate(seed, reps, |rng| sample_vars(&pop.sample(rng, n))[idx]);
+
+        let mean = values.iter().sum::<f64>() / reps as f64;
+        let sd =
+            (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (reps as f64 - 1.0)).sqrt();
+        self.strings[5] = fmt_num(mean);
+        self.strings[6] = fmt_num(sd);
+
+        let lo = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        (self.bars, self.width) = histogram(&values);
+
+        match self.statistic {
+            SampleStatistic::Mean => {
+                if let (Some(mu), Some(sigma)) = (pop.mean(), pop.sd()) {
+                    let normal = mean_dist(mu, sigma, n).map_err(|e| e.to_string())?;
+                    let spread = 4.0 * sigma / (n as f64).sqrt();
+                    let (start, end) = (lo.min(mu - spread), hi.max(mu + spread));
+                    self.curve = (0..=200)
+                        .map(|i| start + (end - start) * i as f64 / 200.0)
+                        .map(|x| [x, normal.pdf(x)])
+                        .collect();
+                    self.strings[7] = fmt_num(mu);
+                    self.strings[8] = fmt_num(sigma / (n as f64).sqrt());
+                }
+            }
+            SampleStatistic::Variance => {
+                if let Some(sigma) = pop.sd() {
+                    self.strings[7] = fmt_num(sigma * sigma);
+                }
+            }
+            SampleStatistic::Median => {}
+        }
+        Ok(())
+    }
+}
+
+impl Widget for &mut SamplingDist {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut changed = ui
+            .horizontal(|ui| {
+                ui.label("Population");
+                let a = ui.radio_value(&mut self.from_data, false, "distribution");
+                a.changed() | ui.radio_value(&mut self.from_data, true, "data").changed()
+            })
+            .inner;
+        let mut resp = if self.from_data {
+            ui.data_box("Population data", &mut self.strings[2])
+        } else {
+            family_ui(ui, &mut self.family, &mut self.strings[..2])
+        };
+        changed |= ui
+            .horizontal(|ui| {
+                ui.label("Statistic");
+                let mut changed = false;
+                for (s, name) in [
+                    (SampleStatistic::Mean, "mean"),
+                    (SampleStatistic::Median, "median"),
+                    (SampleStatistic::Variance, "variance"),
+                ] {
+                    changed |= ui.radio_value(&mut self.statistic, s, name).changed();
+                }
+                changed
+            })
+            .inner;
+        resp = resp.union(ui.add(Slider::new(&mut self.n, 1..=100).text("n")));
+        resp = resp.union(ui.num_box("experiments", &mut self.strings[3]));
+        resp = resp.union(seed_ui(ui, &mut self.strings[4]));
+        if changed {
+            resp.mark_changed();
+        }
+        if resp.changed() || (self.bars.is_empty() && self.strings[9].is_empty()) {
+            self.strings[9] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.num_box("simulated mean", &mut self.strings[5].clone());
+        ui.num_box("simulated sd", &mut self.strings[6].clone());
+        if self.statistic != SampleStatistic::Median {
+            ui.num_box("theoretical mean", &mut self.strings[7].clone());
+        }
+        if self.statistic == SampleStatistic::Mean {
+            ui.num_box("theoretical sd", &mut self.strings[8].clone());
+        }
+        ui.label(RichText::new(&self.strings[9]).color(Color32::DARK_RED));
+
+        if !self.bars.is_empty() {
+            let name = match self.statistic {
+                SampleStatistic::Mean => "Sample means",
+                SampleStatistic::Median => "Sample medians",
+                SampleStatistic::Variance => "Sample variances",
+            };
+            let bars = self
+                .bars
+                .iter()
+                .map(|[x, h]| Bar::new(*x, *h).width(self.width))
+                .collect();
+            Plot::new("Sampling distribution")
+                .height(300.)
+                .legend(Legend::default())
+                .show(ui, |ui| {
+                    ui.bar_chart(BarChart::new(bars).name(name));
+                    if !self.curve.is_empty() {
+                        ui.line(
+                            Line::new(self.curve.clone())
+                                .color(Color32::RED)
+                                .name("Normal from the CLT"),
+                        );
+                    }
+                });
+        }
+        resp
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum IntervalKind {
+    Z,
+    #[default]
+    T,
+}
+
+/// Intervals from repeated samples of a known population, showing how often they catch its mean.
+#[derive(Clone)]
+pub(crate) struct Coverage {
+    family: Family,
+    kind: IntervalKind,
+    intervals: Vec<(f64, f64, bool)>,
+    mean: f64,
+    /// param 1, param 2, sample size, confidence, intervals, seed, coverage, standard error, error
+    strings: [String; 9],
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self {
+            family: Family::Normal,
+            kind: IntervalKind::T,
+            intervals: vec![],
+            mean: 0.0,
+            strings: [
+                "0".to_string(),
+                "1".to_string(),
+                "10".to_string(),
+                "0.95".to_string(),
+                "100".to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+impl Coverage {
+    fn calc(&mut self) -> Result<(), String> {
+        self.intervals.clear();
+        self.strings[6..8].iter_mut().for_each(String::clear);
+        let pop = population(self.family, &self.strings[..2])?;
+        let mean = pop.mean().ok_or("This population has no mean")?;
+        let n = eval(&self.strings[2], "Sample size")?;
+        let confidence = eval(&self.strings[3], "Confidence")?;
+        let reps = eval(&self.strings[4], "Intervals")?;
+        if n < 2.0 || n.fract() != 0.0 {
+            return Err("Sample size must be a whole number of at least 2".to_string());
+        }
+        if reps < 1.0 || reps.fract() != 0.0 {
+            return Err("Intervals must be a positive whole number".to_string());
+        }
+        if confidence <= 0.0 || confidence >= 1.0 {
+            return Err("Confidence must be between 0 and 1".to_string());
+        }
+        let (n, reps) = (n as usize, reps as usize);
+        if n.saturating_mul(reps) > MAX_DRAWS {
+            return Err(format!("At most {MAX_DRAWS} values can be drawn"));
+        }
+        let seed = parse_seed(&self.strings[5])?;
+        let sigma = match self.kind {
+            IntervalKind::Z => pop
+                .sd()
+                .ok_or("This population has no standard deviation")?,
+            IntervalKind::T => 0.0,
+        };
+
+        let crit = match self.kind {
+            IntervalKind::Z => NullDist::Normal,
+            IntervalKind::T => NullDist::T(n as f64 - 1.0),
+        }
+        .inverse_cdf((1.0 + confidence) / 2.0)?;
+
+        self.mean = mean;
+        self.intervals = simulate(seed, reps, |rng| {
+            let vars = sample_vars(&pop.sample(rng, n));
+            let m = vars[3];
+            let sd = match self.kind {
+                IntervalKind::Z => sigma,
+                IntervalKind::T => vars[6],
+            };
+            // A sample with no spread gives a single point
+            let q = crit * sd / (n as f64).sqrt();
+            (m - q, m + q, m - q <= mean && mean <= m + q)
+        });
+        let hits: Vec<bool> = self.intervals.iter().map(|i| i.2).collect();
+        let (p, se) = proportion(&hits);
+        self.strings[6] = format!(
+            "{} of {reps} cover the mean, {}",
+            hits.iter().filter(|h| **h).count(),
+            fmt_num(p)
+        );
+        self.strings[7] = fmt_num(se);
+        Ok(())
+    }
+}
+
+impl Widget for &mut Coverage {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut resp = family_ui(ui, &mut self.family, &mut self.strings[..2]);
+        let changed = ui
+            .horizontal(|ui| {
+                ui.label("Interval");
+                let z = ui.radio_value(&mut self.kind, IntervalKind::Z, "z, sd known");
+                z.changed()
+                    | ui.radio_value(&mut self.kind, IntervalKind::T, "t")
+                        .changed()
+            })
+            .inner;
+        resp = resp.union(ui.num_box("sample size", &mut self.strings[2]));
+        resp = resp.union(ui.num_box("confidence", &mut self.strings[3]));
+        resp = resp.union(ui.num_box("intervals", &mut self.strings[4]));
+        resp = resp.union(seed_ui(ui, &mut self.strings[5]));
+        if changed {
+            resp.mark_changed();
+        }
+        if resp.changed() || (self.intervals.is_empty() && self.strings[8].is_empty()) {
+            self.strings[8] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.num_box("coverage", &mut self.strings[6].clone());
+        ui.num_box("MC std error", &mut self.strings[7].clone());
+        ui.label(RichText::new(&self.strings[8]).color(Color32::DARK_RED));
+
+        if !self.intervals.is_empty() {
+            if self.intervals.len() > MAX_SHOWN {
+                ui.label(format!("Showing the first {MAX_SHOWN} intervals"));
+            }
+            Plot::new("Coverage")
+                .height(400.)
+                .legend(Legend::default())
+                .show(ui, |ui| {
+                    for (i, (lo, hi, hit)) in self.intervals.iter().take(MAX_SHOWN).enumerate() {
+                        let (color, name) = if *hit {
+                            (Color32::LIGHT_BLUE, "Covers the mean")
+                        } else {
+                            (Color32::RED, "Misses the mean")
+                        };
+                        let y = i as f64 + 1.0;
+                        ui.line(
+                            Line::new(vec![[*lo, y], [*hi, y]])
+                                .color(color)
+                                .width(2.)
+                                .name(name),
+                        );
+                    }
+                    ui.vline(
+                        VLine::new(self.mean)
+                            .color(Color32::GRAY)
+                            .name("Population mean"),
+                    );
+                });
+        }
+        resp
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum BootStat {
+    Mean,
+    #[default]
+    Median,
+    TrimmedMean,
+    Sd,
+    Correlation,
+    SdRatio,
+    Expression,
+}
+
+impl BootStat {
+    fn two_columns(&self) -> bool {
+        matches!(self, BootStat::Correlation | BootStat::SdRatio)
+    }
+}
+
+/// A statistic of one or two data columns, the second empty when unused.
+type TwoSampleStat = dyn Fn(&[f64], &[f64]) -> f64;
+
+/// Mean after cutting `TRIM` of the values from each end.
+fn trimmed_mean(v: &[f64]) -> f64 {
+    let mut sorted = v.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let cut = (TRIM * v.len() as f64).floor() as usize;
+    let kept = &sorted[cut..v.len() - cut];
+    kept.iter().sum::<f64>() / kept.len() as f64
+}
+
+/// The values with one left out.
+fn without(v: &[f64], i: usize) -> Vec<f64> {
+    v.iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .map(|(_, x)| *x)
+        .collect()
+}
+
+/// Percentile, basic and BCa intervals from resampling data columns.
+#[derive(Clone)]
+pub(crate) struct Bootstrap {
+    statistic: BootStat,
+    bars: Vec<[f64; 2]>,
+    width: f64,
+    estimate: f64,
+    bounds: [f64; 2],
+    intervals: Vec<Vec<String>>,
+    /// x, y, expression, resamples, confidence, seed, estimate, bootstrap se, error
+    strings: [String; 9],
+}
+
+impl Default for Bootstrap {
+    fn default() -> Self {
+        Self {
+            statistic: BootStat::Median,
+            bars: vec![],
+            width: 1.0,
+            estimate: 0.0,
+            bounds: [0.0, 0.0],
+            intervals: vec![],
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "max - min".to_string(),
+                "2000".to_string(),
+                "0.95".to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+impl Bootstrap {
+    fn calc(&mut self) -> Result<(), String> {
+        self.bars.clear();
+        self.intervals.clear();
+        self.strings[6..8].iter_mut().for_each(String::clear);
+        let x = parse_data(&self.strings[0])?;
+        let y = if self.statistic.two_columns() {
+            parse_data(&self.strings[1])?
+        } else {
+            vec![]
+        };
+        if x.len() < 3 || (self.statistic.two_columns() && y.len() < 3) {
+            return Err("Each column needs at least 3 values".to_string());
+        }
+        let paired = self.statistic == BootStat::Correlation;
+        if paired && x.len() != y.len() {
+            return Err("x and y must be the same length".to_string());
+        }
+        let reps = eval(&self.strings[3], "Resamples")?;
+        if reps < 10.0 || reps.fract() != 0.0 {
+            return Err("Resamples must be a whole number of at least 10".to_string());
+        }
+        let reps = reps as usize;
+        if reps.saturating_mul(x.len() + y.len()) > MAX_DRAWS {
+            return Err(format!("At most {MAX_DRAWS} values can be drawn"));
+        }
+        let confidence = eval(&self.strings[4], "Confidence")?;
+        if confidence <= 0.0 || confidence >= 1.0 {
+            return Err("Confidence must be between 0 and 1".to_string());
+        }
+        let seed = parse_seed(&self.strings[5])?;
+        let stat: Box<TwoSampleStat> = match self.statistic {
+            BootStat::Mean => Box::new(|x, _| sample_vars(x)[3]),
+            BootStat::Median => Box::new(|x, _| sample_vars(x)[4]),
+            BootStat::TrimmedMean => Box::new(|x, _| trimmed_mean(x)),
+            BootStat::Sd => Box::new(|x, _| sample_vars(x)[6]),
+            BootStat::Correlation => Box::new(pearson),
+            BootStat::SdRatio => Box::new(|x, y| sample_vars(x)[6] / sample_vars(y)[6]),
+            BootStat::Expression => {
+                let e = self.strings[2]
+                    .parse::<Expr>()
+                    .and_then(|e| e.bindn(&STAT_VARS[1..]))
+                    .map_err(|e| format!("Statistic is invalid: {e}"))?;
+                // The first value means nothing once the data is resampled
+                Box::new(move |x, _| e(&sample_vars(x)[1..]))
+            }
+        };
+        let estimate = stat(&x, &y);
+        if !estimate.is_finite() {
+            return Err("The statistic can't be found for this data".to_string());
+        }
+
+        let mut boots: Vec<f64> = simulate(seed, reps, |rng| {
+            if paired {
+                let idx: Vec<usize> = (0..x.len()).map(|_| rng.gen_range(0..x.len())).collect();
+                let xs: Vec<f64> = idx.iter().map(|i| x[*i]).collect();
+                let ys: Vec<f64> = idx.iter().map(|i| y[*i]).collect();
+                stat(&xs, &ys)
+            } else {
+                let mut pick = |v: &[f64]| -> Vec<f64> {
+                    (0..v.len()).map(|_| v[rng.gen_range(0..v.len())]).collect()
+                };
+                let xs = pick(&x);
+                let ys = pick(&y);
+                stat(&xs, &ys)
+            }
+        })
+        .into_iter()
+        .filter(|b| b.is_finite())
+        .collect();
+        if boots.len() < 10 {
+            return Err("Too few resamples gave a value for the statistic".to_string());
+        }
+        boots.sort_by(|a, b| a.total_cmp(b));
+        let b = boots.len() as f64;
+        let mean = boots.iter().sum::<f64>() / b;
+        let se = (boots.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (b - 1.0)).sqrt();
+
+        let alpha = 1.0 - confidence;
+        let (lo, hi) = (
+            quantile(&boots, alpha / 2.0),
+            quantile(&boots, 1.0 - alpha / 2.0),
+        );
+
+        // BCa: bias from the share of resamples below the estimate, acceleration from the jackknife
+        let snd = Normal::new(0.0, 1.0).expect("SND cant fail");
+        let below = boots.iter().filter(|v| **v < estimate).count() as f64 / b;
+        let z0 = snd.inverse_cdf(below);
+        // Leave-one-out values for each resampled group, paired data being a single group
+        let groups: Vec<Vec<f64>> = if paired {
+            vec![(0..x.len())
+                .map(|i| stat(&without(&x, i), &without(&y, i)))
+                .collect()]
+        } else {
+            vec![
+                (0..x.len()).map(|i| stat(&without(&x, i), &y)).collect(),
+                (0..y.len()).map(|i| stat(&x, &without(&y, i))).collect(),
+            ]
+        };
+        // Influence values scaled by each group's size, as groups are resampled separately
+        let (mut num, mut den) = (0.0, 0.0);
+        for jack in groups.iter().filter(|g| !g.is_empty()) {
+            let n = jack.len() as f64;
+            let jm = jack.iter().sum::<f64>() / n;
+            for j in jack {
+                let u = (n - 1.0) * (jm - j) / n;
+                num += u.powi(3);
+                den += u.powi(2);
+            }
+        }
+        let a = num / (6.0 * den.powf(1.5));
+        let adjust = |p: f64| {
+            let z = snd.inverse_cdf(p);
+            snd.cdf(z0 + (z0 + z) / (1.0 - a * (z0 + z)))
+        };
+        let bca = (
+            quantile(&boots, adjust(alpha / 2.0)),
+            quantile(&boots, adjust(1.0 - alpha / 2.0)),
+        );
+        let show = |v: f64| {
+            if v.is_finite() {
+                fmt_num(v)
+            } else {
+                "".to_string()
+            }
+        };
+        self.intervals = [
+            ("Percentile", lo, hi),
+            ("Basic", 2.0 * estimate - hi, 2.0 * estimate - lo),
+            ("BCa", bca.0, bca.1),
+        ]
+        .iter()
+        .map(|(name, l, u)| vec![name.to_string(), show(*l), show(*u)])
+        .collect();
+
+        self.estimate = estimate;
+        self.bounds = [lo, hi];
+        (self.bars, self.width) = histogram(&boots);
+        self.strings[6] = fmt_num(estimate);
+        self.strings[7] = fmt_num(se);
+        Ok(())
+    }
+}
+
+impl Widget for &mut Bootstrap {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let changed = ui
+            .horizontal_wrapped(|ui| {
+                ui.label("Statistic");
+                let mut changed = false;
+                for (s, name) in [
+                    (BootStat::Mean, "mean"),
+                    (BootStat::Median, "median"),
+                    (BootStat::TrimmedMean, "10% trimmed mean"),
+                    (BootStat::Sd, "sd"),
+                    (BootStat::Correlation, "correlation"),
+                    (BootStat::SdRatio, "ratio of sds"),
+                    (BootStat::Expression, "expression"),
+                ] {
+                    changed |= ui.radio_value(&mut self.statistic, s, name).changed();
+                }
+                changed
+            })
+            .inner;
+        let mut resp = ui
+            .horizontal(|ui| {
+                let resp = ui.data_box("x", &mut self.strings[0]);
+                if self.statistic.two_columns() {
+                    resp.union(ui.data_box("y", &mut self.strings[1]))
+                } else {
+                    resp
+                }
+            })
+            .inner;
+        if self.statistic == BootStat::Expression {
+            ui.label("The statistic can use n, sum, mean, median, var, sd, min and max");
+            resp = resp.union(ui.num_box("statistic", &mut self.strings[2]));
+        }
+        resp = resp.union(ui.num_box("resamples", &mut self.strings[3]));
+        resp = resp.union(ui.num_box("confidence", &mut self.strings[4]));
+        resp = resp.union(seed_ui(ui, &mut self.strings[5]));
+        if changed {
+            resp.mark_changed();
+        }
+        if resp.changed() {
+            self.strings[8] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.num_box("estimate", &mut self.strings[6].clone());
+        ui.num_box("bootstrap se", &mut self.strings[7].clone());
+        ui.label(RichText::new(&self.strings[8]).color(Color32::DARK_RED));
+
+        if !self.bars.is_empty() {
+            ui.table(
+                "Bootstrap intervals",
+                &["Method", "Lower", "Upper"],
+                &self.intervals,
+            );
+            let bars = self
+                .bars
+                .iter()
+                .map(|[x, h]| Bar::new(*x, *h).width(self.width))
+                .collect();
+            Plot::new("Bootstrap distribution")
+                .height(300.)
+                .legend(Legend::default())
+                .show(ui, |ui| {
+                    ui.bar_chart(BarChart::new(bars).name("Bootstrap statistics"));
+                    ui.vline(
+                        VLine::new(self.estimate)
+                            .color(Color32::RED)
+                            .name("Estimate"),
+                    );
+                    for b in self.bounds {
+                        ui.vline(
+                            VLine::new(b)
+                                .color(Color32::GRAY)
+                                .name("Percentile interval"),
+                        );
+                    }
+                });
+        }
+        resp
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum PermStat {
+    #[default]
+    MeanDiff,
+    MedianDiff,
+    Correlation,
+}
+
+/// Randomization test that reshuffles group labels, or pairings for a correlation.
+#[derive(Clone)]
+pub(crate) struct Permutation {
+    statistic: PermStat,
+    alt: Alternative,
+    bars: Vec<[f64; 2]>,
+    width: f64,
+    observed: f64,
+    /// x, y, reshuffles, seed, observed, p value, method, error
+    strings: [String; 8],
+}
+
+impl Default for Permutation {
+    fn default() -> Self {
+        Self {
+            statistic: PermStat::MeanDiff,
+            alt: Alternative::TwoSided,
+            bars: vec![],
+            width: 1.0,
+            observed: 0.0,
+            strings: [
+                "".to_string(),
+                "".to_string(),
+                "10000".to_string(),
+                "1".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ],
+        }
+    }
+}
+
+impl Permutation {
+    fn calc(&mut self) -> Result<(), String> {
+        self.bars.clear();
+        self.strings[4..7].iter_mut().for_each(String::clear);
+        let x = parse_data(&self.strings[0])?;
+        let y = parse_data(&self.strings[1])?;
+        if x.len() < 2 || y.len() < 2 {
+            return Err("Each column needs at least 2 values".to_string());
+        }
+        let corr = self.statistic == PermStat::Correlation;
+        if corr && x.len() != y.len() {
+            return Err("x and y must be the same length".to_string());
+        }
+        let reps = eval(&self.strings[2], "Reshuffles")?;
+        if reps < 10.0 || reps.fract() != 0.0 {
+            return Err("Reshuffles must be a whole number of at least 10".to_string());
+        }
+        let reps = reps as usize;
+        if reps.saturating_mul(x.len() + y.len()) > MAX_DRAWS {
+            return Err(format!("At most {MAX_DRAWS} values can be drawn"));
+        }
+        let seed = parse_seed(&self.strings[3])?;
+        let stat = |a: &[f64], b: &[f64]| match self.statistic {
+            PermStat::MeanDiff => sample_vars(a)[3] - sample_vars(b)[3],
+            PermStat::MedianDiff => sample_vars(a)[4] - sample_vars(b)[4],
+            PermStat::Correlation => pearson(a, b),
+        };
+        let observed = stat(&x, &y);
+        if !observed.is_finite() {
+            return Err("The statistic can't be found for this data".to_string());
+        }
+
+        let n = x.len();
+        let total = n + y.len();
+        let arrangements = if corr {
+            (1..=n).map(|i| i as f64).product::<f64>()
+        } else {
+            (0..n)
+                .map(|i| (total - i) as f64 / (i + 1) as f64)
+                .product::<f64>()
+        };
+        let exact = arrangements <= EXACT_PERM_MAX;
+        let mut null = vec![];
+        if corr && exact {
+            for_each_permutation(&mut y.clone(), |p| null.push(pearson(&x, p)));
+        } else if corr {
+            null = simulate(seed, reps, |rng| {
+                let mut p = y.clone();
+                p.shuffle(rng);
+                pearson(&x, &p)
+            });
+        } else {
+            let pooled = [x, y].concat();
+            if exact {
+                let mut chosen = vec![false; total];
+                for_each_combination(total, n, |idx| {
+                    chosen.iter_mut().for_each(|c| *c = false);
+                    idx.iter().for_each(|i| chosen[*i] = true);
+                    let (mut a, mut b) = (vec![], vec![]);
+                    for (v, c) in pooled.iter().zip(&chosen) {
+                        if *c {
+                            a.push(*v);
+                        } else {
+                            b.push(*v);
+                        }
+                    }
+                    null.push(stat(&a, &b));
+                });
+            } else {
+                null = simulate(seed, reps, |rng| {
+                    let mut p = pooled.clone();
+                    p.shuffle(rng);
+                    let (a, b) = p.split_at(n);
+                    stat(a, b)
+                });
+            }
+        }
+        null.retain(|v| v.is_finite());
+
+        let tol = 1e-9 * observed.abs().max(1.0);
+        let lower = null.iter().filter(|v| **v <= observed + tol).count() as f64;
+        let upper = null.iter().filter(|v| **v >= observed - tol).count() as f64;
+        let count = null.len() as f64;
+        let (lower, upper) = if exact {
+            (lower / count, upper / count)
+        } else {
+            // Counting the observed arrangement keeps the p value above 0
+            ((lower + 1.0) / (count + 1.0), (upper + 1.0) / (count + 1.0))
+        };
+        self.observed = observed;
+        self.strings[4] = fmt_num(observed);
+        self.strings[5] = fmt_num(self.alt.pval(lower, upper));
+        self.strings[6] = if exact {
+            format!("Exact, over all {count} arrangements")
+        } else {
+            format!("Monte Carlo, over {count} reshuffles")
+        };
+        (self.bars, self.width) = histogram(&null);
+        Ok(())
+    }
+}
+
+impl Widget for &mut Permutation {
+    fn ui(self, ui: &mut Ui) -> egui::Response {
+        let mut changed = ui
+            .horizontal(|ui| {
+                ui.label("Statistic");
+                let mut changed = false;
+                for (s, name) in [
+                    (PermStat::MeanDiff, "difference in means"),
+                    (PermStat::MedianDiff, "difference in medians"),
+                    (PermStat::Correlation, "correlation"),
+                ] {
+                    changed |= ui.radio_value(&mut self.statistic, s, name).changed();
+                }
+                changed
+            })
+            .inner;
+        changed |= ui.alt_radio(&mut self.alt);
+        let (a, b) = if self.statistic == PermStat::Correlation {
+            ("x", "y")
+        } else {
+            ("Group 1", "Group 2")
+        };
+        let mut resp = ui
+            .horizontal(|ui| {
+                let resp = ui.data_box(a, &mut self.strings[0]);
+                resp.union(ui.data_box(b, &mut self.strings[1]))
+            })
+            .inner;
+        resp = resp.union(ui.num_box("reshuffles", &mut self.strings[2]));
+        resp = resp.union(seed_ui(ui, &mut self.strings[3]));
+        if changed {
+            resp.mark_changed();
+        }
+        if resp.changed() {
+            self.strings[7] = match self.calc() {
+                Ok(()) => "".to_string(),
+                Err(e) => e,
+            };
+        }
+        ui.num_box("observed", &mut self.strings[4].clone());
+        ui.num_box("p value", &mut self.strings[5].clone());
+        ui.label(&self.strings[6]);
+        ui.label(RichText::new(&self.strings[7]).color(Color32::DARK_RED));
+
+        if !self.bars.is_empty() {
+            let bars = self
+                .bars
+                .iter()
+                .map(|[x, h]| Bar::new(*x, *h).width(self.width))
+                .collect();
+            Plot::new("Permutation distribution")
+                .height(300.)
+                .legend(Legend::default())
+                .show(ui, |ui| {
+                    ui.bar_chart(BarChart::new(bars).name("Null distribution"));
+                    ui.vline(
+                        VLine::new(self.observed)
+                            .color(Color32::RED)
+                            .name("Observed"),
+                    );
+                });
+        }
+        resp
+    }
+}